@@ -0,0 +1,325 @@
+//! Headless map generator for tuning generation without opening the game.
+//!
+//! Runs the same generator the game uses and writes `<out>.json` with every
+//! tile, deposit, river and player start plus `<out>.png`, a preview coloured
+//! by terrain with starts marked, then prints how much of the map each
+//! terrain covers and how many deposits of each kind were placed.
+//!
+//! ```text
+//! cargo run --bin hexgen -- --seed 42 --preset archipelago --out maps/archipelago
+//! ```
+//!
+//! Flags: `--seed <n>` (random if left out), `--preset <name>`, `--players <n>`,
+//! `--out <path>` (default `map`), `--scale <pixels>` (hex size in the
+//! preview, default 8), `--orientation <pointy|flat>` (default pointy) and
+//! `--terrains <file>` (default `assets/data/terrains.terrain.ron`). `--help` lists them; any other
+//! argument is an error.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use bevy::math::Vec2;
+use bevy::utils::HashMap;
+use image::{Rgb, RgbImage};
+use serde::Serialize;
+use bevy_hex_grid::systems::{AtlasManifest, GeneratedMap, MapGenConfig, MapPreset};
+use bevy_hex_grid::terrain::{DepositId, TerrainId, TerrainRegistry, Yields, TERRAIN_DEFINITIONS_PATH};
+use bevy_hex_grid::utils::{HexLayout, HexOrientation};
+
+/// Colour of pixels outside the map
+const BACKGROUND: Rgb<u8> = Rgb([16, 16, 24]);
+const RIVER_COLOR: Rgb<u8> = Rgb([40, 90, 230]);
+const START_COLOR: Rgb<u8> = Rgb([255, 40, 40]);
+
+/// Every flag hexgen takes, each followed by a value
+const FLAGS: [&str; 7] = ["--seed", "--preset", "--players", "--out", "--scale", "--orientation", "--terrains"];
+
+#[derive(Serialize)]
+struct MapExport<'a> {
+    seed: u64,
+    preset: &'static str,
+    tiles: Vec<TileExport<'a>>,
+    /// Each river as a list of edges, each edge as the two hexes it separates
+    rivers: Vec<Vec<[[i32; 2]; 2]>>,
+    /// Start hex of each player
+    starts: Vec<[i32; 2]>,
+}
+
+#[derive(Serialize)]
+struct TileExport<'a> {
+    q: i32,
+    r: i32,
+    terrain: &'a str,
+    variant: usize,
+    elevation: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deposit: Option<&'a str>,
+    yields: Yields,
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("hexgen: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let Some(args) = parse_args(std::env::args().skip(1))? else {
+        println!("{}", usage());
+        return Ok(());
+    };
+    let arg_value = |flag: &str| args.get(flag).cloned();
+    let seed = match arg_value("--seed") {
+        Some(seed) => seed.parse().map_err(|_| format!("invalid seed {:?}", seed))?,
+        None => rand::random(),
+    };
+    let preset = match arg_value("--preset") {
+        Some(name) => MapPreset::from_name(&name).ok_or_else(|| {
+            let names: Vec<_> = MapPreset::ALL.iter().map(|preset| preset.name()).collect();
+            format!("unknown preset {:?}, expected one of: {}", name, names.join(", "))
+        })?,
+        None => MapPreset::default(),
+    };
+    let out = arg_value("--out").unwrap_or_else(|| "map".to_string());
+    let scale: f32 = match arg_value("--scale") {
+        Some(scale) => scale.parse()
+            .ok()
+            .filter(|scale: &f32| scale.is_finite() && *scale > 0.0)
+            .ok_or_else(|| format!("invalid scale {:?}, expected a positive number of pixels", scale))?,
+        None => 8.0,
+    };
+    let terrains_path = arg_value("--terrains")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new("assets").join(TERRAIN_DEFINITIONS_PATH));
+
+    let source = std::fs::read_to_string(&terrains_path)
+        .map_err(|error| format!("could not read {}: {}", terrains_path.display(), error))?;
+    let registry = TerrainRegistry::from_ron(&source)
+        .map_err(|error| format!("invalid terrain definitions in {}: {}", terrains_path.display(), error))?;
+
+    let mut config = MapGenConfig::for_preset(preset, seed);
+    if let Some(players) = arg_value("--players") {
+        config.start.players = players.parse().map_err(|_| format!("invalid player count {:?}", players))?;
+    }
+    let orientation = match arg_value("--orientation") {
+        Some(name) => HexOrientation::from_name(&name)
+            .ok_or_else(|| format!("unknown orientation {:?}, expected pointy or flat", name))?,
+        None => HexOrientation::POINTY,
+    };
+    let layout = HexLayout::new(orientation, scale);
+    let map = preset.generator().generate(&config, &registry, &layout);
+
+    // Append rather than `with_extension`, which would cut "maps/v1.2"
+    // down to "maps/v1"
+    let json_path = PathBuf::from(format!("{}.json", out));
+    write_json(&json_path, &map, &config, &registry)?;
+    let png_path = PathBuf::from(format!("{}.png", out));
+    let colors = terrain_colors(&registry, terrains_path.parent().and_then(Path::parent));
+    render_preview(&map, &layout, &colors).save(&png_path)?;
+
+    println!("Map {} (seed {}): wrote {} and {}", preset.name(), seed, json_path.display(), png_path.display());
+    print_stats(&map, &registry);
+    Ok(())
+}
+
+/// Flag values by flag, or `None` if help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<HashMap<&'static str, String>>, String> {
+    let mut values = HashMap::new();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Ok(None);
+        }
+        let Some(&flag) = FLAGS.iter().find(|&&flag| flag == arg) else {
+            return Err(format!("unknown argument {:?}\n\n{}", arg, usage()));
+        };
+        let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+        values.insert(flag, value);
+    }
+    Ok(Some(values))
+}
+
+fn usage() -> String {
+    let presets: Vec<_> = MapPreset::ALL.iter().map(|preset| preset.name()).collect();
+    format!(
+        concat!(
+            "Usage: hexgen [options]\n",
+            "\n",
+            "Options:\n",
+            "  --seed <n>         map seed, random if left out\n",
+            "  --preset <name>    one of: {} (default {})\n",
+            "  --players <n>      players to place starts for\n",
+            "  --out <path>       output path without extension (default map)\n",
+            "  --scale <pixels>   hex size in the preview (default 8)\n",
+            "  --orientation <o>  pointy or flat (default pointy)\n",
+            "  --terrains <file>  terrain definitions (default assets/{})\n",
+            "  -h, --help         show this message",
+        ),
+        presets.join(", "),
+        MapPreset::default().name(),
+        TERRAIN_DEFINITIONS_PATH,
+    )
+}
+
+fn write_json(path: &Path, map: &GeneratedMap, config: &MapGenConfig, registry: &TerrainRegistry) -> Result<(), Box<dyn Error>> {
+    let manifest = AtlasManifest::new(registry);
+    let export = MapExport {
+        seed: config.seed,
+        preset: config.preset.name(),
+        tiles: map.tiles.iter()
+            .map(|tile| TileExport {
+                q: tile.hex.q,
+                r: tile.hex.r,
+                terrain: registry.name(tile.terrain),
+                variant: manifest.entry(tile.texture_variant).map_or(0, |(_, variant)| variant),
+                elevation: tile.elevation,
+                deposit: tile.deposit.map(|deposit| registry.deposit_name(deposit)),
+                yields: tile.yields,
+            })
+            .collect(),
+        rivers: map.rivers.iter()
+            .map(|river| {
+                river.edges.iter()
+                    .map(|edge| {
+                        let (a, b) = edge.hexes();
+                        [[a.q, a.r], [b.q, b.r]]
+                    })
+                    .collect()
+            })
+            .collect(),
+        starts: map.start_positions.iter().map(|hex| [hex.q, hex.r]).collect(),
+    };
+
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &export)?;
+    Ok(())
+}
+
+/// Preview colour of every terrain: the average colour of its first texture
+/// times its tint, or a grey when the texture can't be read
+fn terrain_colors(registry: &TerrainRegistry, assets_dir: Option<&Path>) -> HashMap<TerrainId, Rgb<u8>> {
+    let assets_dir = assets_dir.unwrap_or(Path::new("assets"));
+    registry.iter()
+        .map(|(id, terrain)| {
+            let average = terrain.textures.first()
+                .and_then(|texture| image::open(assets_dir.join(texture)).ok())
+                .and_then(|texture| {
+                    // Weight by alpha so the transparent corners don't count
+                    let mut sum = [0.0; 3];
+                    let mut weight = 0.0;
+                    for pixel in texture.to_rgba8().pixels() {
+                        let alpha = pixel[3] as f32 / 255.0;
+                        for channel in 0..3 {
+                            sum[channel] += pixel[channel] as f32 * alpha;
+                        }
+                        weight += alpha;
+                    }
+                    (weight > 0.0).then(|| sum.map(|channel| channel / weight))
+                })
+                .unwrap_or([128.0; 3]);
+
+            let tint = terrain.tint.unwrap_or([1.0; 3]);
+            let color = std::array::from_fn(|channel| (average[channel] * tint[channel]).clamp(0.0, 255.0) as u8);
+            (id, Rgb(color))
+        })
+        .collect()
+}
+
+/// Draw every tile as a filled hex in its terrain colour, with rivers and
+/// player starts on top
+fn render_preview(map: &GeneratedMap, layout: &HexLayout, colors: &HashMap<TerrainId, Rgb<u8>>) -> RgbImage {
+    let half_extent = layout.hex_extent() / 2.0;
+    let (min, max) = map.tiles.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), tile| (min.min(tile.position.truncate()), max.max(tile.position.truncate())),
+    );
+    let (min, max) = if map.tiles.is_empty() {
+        (Vec2::ZERO, Vec2::ONE)
+    } else {
+        (min - half_extent, max + half_extent)
+    };
+
+    // Image rows run top to bottom, world y runs bottom to top
+    let to_world = |x: f32, y: f32| Vec2::new(min.x + x, max.y - y);
+    let to_pixel = |position: Vec2| (position.x - min.x, max.y - position.y);
+
+    let terrain_at: HashMap<_, _> = map.tiles.iter().map(|tile| (tile.hex, tile.terrain)).collect();
+    let size = (max - min).ceil();
+    let mut image = RgbImage::from_pixel(size.x as u32, size.y as u32, BACKGROUND);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let position = to_world(x as f32 + 0.5, y as f32 + 0.5);
+        if let Some(terrain) = terrain_at.get(&layout.world_to_hex(position.extend(0.0))) {
+            *pixel = colors[terrain];
+        }
+    }
+
+    for river in &map.rivers {
+        for &edge in &river.edges {
+            let (start, end) = layout.edge_to_world(edge);
+            let steps = (end - start).length().ceil().max(1.0) as usize * 2;
+            for step in 0..=steps {
+                let (x, y) = to_pixel(start.lerp(end, step as f32 / steps as f32));
+                if x >= 0.0 && y >= 0.0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                    image.put_pixel(x as u32, y as u32, RIVER_COLOR);
+                }
+            }
+        }
+    }
+
+    // A dot in the middle of each start hex
+    let radius = (half_extent.min_element() / 2.0).max(1.0);
+    for &start in &map.start_positions {
+        let (center_x, center_y) = to_pixel(layout.hex_to_world(start).truncate());
+        let (min_x, max_x) = ((center_x - radius).max(0.0) as u32, (center_x + radius) as u32);
+        let (min_y, max_y) = ((center_y - radius).max(0.0) as u32, (center_y + radius) as u32);
+        for y in min_y..=max_y.min(image.height() - 1) {
+            for x in min_x..=max_x.min(image.width() - 1) {
+                if Vec2::new(x as f32 + 0.5 - center_x, y as f32 + 0.5 - center_y).length() <= radius {
+                    image.put_pixel(x, y, START_COLOR);
+                }
+            }
+        }
+    }
+
+    image
+}
+
+/// Print the share of tiles each terrain covers, most common first
+fn print_stats(map: &GeneratedMap, registry: &TerrainRegistry) {
+    let mut counts: HashMap<TerrainId, usize> = HashMap::new();
+    for tile in &map.tiles {
+        *counts.entry(tile.terrain).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|(a_id, a), (b_id, b)| b.cmp(a).then(a_id.cmp(b_id)));
+
+    let total = map.tiles.len().max(1) as f32;
+    let percent = |count: usize| 100.0 * count as f32 / total;
+    println!("{} tiles, {} rivers", map.tiles.len(), map.rivers.len());
+    for (id, count) in &counts {
+        println!("  {:<16} {:>6} {:>6.1}%", registry.name(*id), count, percent(*count));
+    }
+
+    let water: usize = counts.iter()
+        .filter(|(id, _)| registry.is_water(*id))
+        .map(|(_, count)| count)
+        .sum();
+    println!("  {:<16} {:>6} {:>6.1}%", "(all water)", water, percent(water));
+    println!("  {:<16} {:>6} {:>6.1}%", "(all land)", map.tiles.len() - water, percent(map.tiles.len() - water));
+
+    let mut deposits: HashMap<DepositId, usize> = HashMap::new();
+    for deposit in map.tiles.iter().filter_map(|tile| tile.deposit) {
+        *deposits.entry(deposit).or_default() += 1;
+    }
+    println!("{} deposits", deposits.values().sum::<usize>());
+    for (id, _) in registry.deposits() {
+        println!("  {:<16} {:>6}", registry.deposit_name(id), deposits.get(&id).copied().unwrap_or(0));
+    }
+
+    let total: Yields = map.tiles.iter().map(|tile| tile.yields).sum();
+    println!("Total yields: {}", total);
+
+    println!("{} player starts", map.start_positions.len());
+    for (player, start) in map.start_positions.iter().enumerate() {
+        println!("  player {:<9} {}", player + 1, start);
+    }
+}
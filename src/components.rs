@@ -3,16 +3,11 @@ use bevy::prelude::*;
 use bevy::ui::Style;
 
 use crate::resources::UnitTextureSet;
-use crate::utils::axial_to_world;
+use crate::terrain::{DepositId, TerrainId, Yields};
+use crate::utils::{Hex, HexEdge};
 use crate::constants::HEX_SIZE;
 
 
-#[derive(Component, Clone)]
-pub struct Terrain {
-    pub name: String,
-    pub texture_handles: Vec<Handle<Image>>,
-}
-
 #[derive(Component)]
 pub struct SelectionVisualization;
 
@@ -23,25 +18,76 @@ pub struct ContextMenuItem;
 #[derive(Component)]
 pub struct UnitInfoButton;
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct Tile {
     pub id: i32,
-    pub q: i32,
-    pub r: i32,
+    pub hex: Hex,
     pub position: Vec3,
-    pub terrain: String,
-    pub texture_variant: usize,  // Just store the index
+    pub terrain: TerrainId,
+    /// Index of the tile's texture in the terrain atlas, see
+    /// [`AtlasManifest`](crate::systems::AtlasManifest)
+    pub texture_variant: usize,
+    /// Height above sea level; negative under water
+    pub elevation: f32,
+    /// Resource deposit on the tile, if any
+    pub deposit: Option<DepositId>,
+    /// What the tile produces each turn, deposit and river included
+    pub yields: Yields,
 }
 
-#[derive(Component)]
-pub struct HexSprite {
-    pub orientation: f32,
+/// Overlay sprite drawn along a river edge
+#[derive(Component, Debug, Clone, Copy)]
+pub struct RiverOverlay {
+    pub edge: HexEdge,
+}
+
+/// Icon drawn over a tile holding a resource deposit
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DepositIcon {
+    pub hex: Hex,
+    pub deposit: DepositId,
 }
 
+/// Sprite colour to return to when a selection highlight is cleared
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BaseTint(pub Color);
+
+/// Colour a tile is drawn with. Tiles have no sprite of their own; changing
+/// this rebuilds the mesh of the chunk the tile is drawn in.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct TileTint(pub Color);
+
+/// One mesh drawing every tile in a chunk of the map
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HexChunk {
+    pub coord: IVec2,
+}
+
+/// Mesh of lines between every tile, shown by the grid overlay
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GridOverlayMesh;
+
+/// Dot, line or label of the planned path drawn for the selected unit
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PathMarker;
+
+/// Debug label showing the axial coordinates of a hex
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CoordinateLabel {
+    pub hex: Hex,
+}
+
+/// Player controlling a unit, counted from 0
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Owner(pub usize);
+
 #[derive(Component)]
 pub struct UnitState {
     pub is_moving: bool,
-    pub target_position: Option<(i32, i32)>,
+    pub target_position: Option<Hex>,
+    /// Hexes still to walk through on the way to `target_position`, next
+    /// one first
+    pub path: Vec<Hex>,
 }
 
 
@@ -57,7 +103,7 @@ pub struct GameEntity {
     pub name: String,
     pub description: String,
     pub entity_type: EntityType,
-    pub position: (i32, i32),
+    pub position: Hex,
     pub sprite: Handle<Image>,
 }
 
@@ -157,12 +203,23 @@ pub struct UiRoot;
 #[derive(Component)]
 pub struct PlayerInfoPanel;
 
+#[derive(Component)]
+pub struct MapSeedText;
+
 #[derive(Component)]
 pub struct UnitInfoPanel;
 
 #[derive(Component)]
 pub struct UnitInfoText;
 
+/// Panel following the cursor with the hovered tile's terrain, deposit and
+/// yields
+#[derive(Component)]
+pub struct TileTooltip;
+
+#[derive(Component)]
+pub struct TileTooltipText;
+
 #[derive(Component)]
 pub struct DetailedMenuText;
 // New components for the detailed menu
@@ -6,23 +6,84 @@ pub mod grid {
     pub const HEX_SIZE: f32 = 30.0;
     /// Radius of the grid in hexes
     pub const GRID_RADIUS: i32 = 58;
+    /// Hexes along each side of a render chunk, see
+    /// [`crate::systems::TileChunks`]
+    pub const CHUNK_SIZE: i32 = 16;
+    /// How far a neighbouring terrain bleeds into a tile, as a fraction of
+    /// the way from an edge to the centre
+    pub const TERRAIN_BLEND_WIDTH: f32 = 0.35;
+    /// Share of the tile art, around its centre, that blended edges sample
+    /// from, keeping clear of the outline drawn around the art
+    pub const TERRAIN_BLEND_SAMPLE: f32 = 0.6;
     /// Square root of 3 (precalculated for efficiency)
     pub const SQRT_3: f32 = 1.7320508;
 }
 
 
 
+/// Elevation constants
+pub mod elevation {
+    /// Height difference, in heightmap units, that makes one level
+    pub const ELEVATION_STEP: f32 = 0.25;
+    /// Extra movement points for each level climbed
+    pub const CLIMB_COST_PER_LEVEL: u32 = 1;
+    /// Levels of height needed for each extra tile of sight
+    pub const LEVELS_PER_VISION_BONUS: i32 = 2;
+    /// Height at which land tiles reach full brightness
+    pub const ELEVATION_SHADE_RANGE: f32 = 1.5;
+}
+
+/// River constants
+pub mod river {
+    /// Extra movement points to cross a river
+    pub const RIVER_CROSSING_COST: u32 = 1;
+    /// Overlay width for a single river
+    pub const RIVER_WIDTH: f32 = 3.0;
+    /// Overlay width where several rivers have joined
+    pub const RIVER_MAX_WIDTH: f32 = 8.0;
+    /// Extra food on land tiles a river flows past
+    pub const RIVER_FOOD_BONUS: u32 = 1;
+}
+
+/// Resource deposit constants
+pub mod deposit {
+    /// Size of a deposit icon relative to the tile sprite
+    pub const DEPOSIT_ICON_SCALE: f32 = 0.45;
+}
+
 /// Input constants
 pub mod input {
     /// Threshold for detecting drag motion
     pub const DRAG_THRESHOLD: f32 = 1.0;
     /// Camera movement speed
     pub const CAMERA_SPEED: f32 = 500.0;
+    /// Shows or hides the lines between tiles
+    pub const GRID_OVERLAY_KEY: bevy::prelude::KeyCode = bevy::prelude::KeyCode::G;
+    /// Shows or hides the coordinates written on each hex
+    pub const COORDINATE_LABELS_KEY: bevy::prelude::KeyCode = bevy::prelude::KeyCode::C;
+}
+
+/// Unit constants
+pub mod unit {
+    use crate::components::UnitType;
+
+    /// Units every player starts with, placed on and around their start hex
+    pub const STARTING_UNITS: [UnitType; 3] = [UnitType::Warrior, UnitType::Archer, UnitType::ElvenArcher];
 }
 
 
 pub const TERRAIN_Z: f32 = 0.0;
 pub const UNIT_Z: f32 = 1.0;
+/// River overlays sit between tiles and units
+pub const RIVER_Z: f32 = 0.5;
+/// Deposit icons sit above rivers and below units
+pub const DEPOSIT_Z: f32 = 0.6;
+/// The planned path sits above deposit icons and below units
+pub const PATH_PREVIEW_Z: f32 = 0.9;
+/// Grid lines sit above rivers and below deposit icons
+pub const GRID_OVERLAY_Z: f32 = 0.55;
+/// Coordinate labels sit above everything else on the map
+pub const COORDINATE_LABEL_Z: f32 = 1.5;
 
 pub const HOVER_THRESHOLD_SQUARED: f32 = 25.0; // 5.0 squared
 pub const DRAG_THRESHOLD_SQUARED: f32 = 25.0; // 5.0 squared
@@ -33,6 +94,14 @@ pub mod selection {
 
     use super::*;
     use bevy::prelude::*;
+
+    /// Share of the way to white that tiles in the selected unit's
+    /// movement range are lightened
+    pub const RANGE_LIGHTEN: f32 = 0.25;
+    /// Weights of red, green and blue in the grey of out-of-range tiles
+    pub const RANGE_GREY_WEIGHTS: [f32; 3] = [0.3, 0.59, 0.11];
+    /// Brightness of the grey that out-of-range tiles are drawn in
+    pub const RANGE_GREY_SHADE: f32 = 0.55;
     
     #[derive(Clone, Copy)]
     pub struct SelectionThreshold {
@@ -90,4 +159,8 @@ pub mod selection {
 
 // Re-export commonly used constants at the module level
 pub use grid::*;
-pub use input::*;
\ No newline at end of file
+pub use elevation::*;
+pub use river::*;
+pub use deposit::*;
+pub use input::*;
+pub use unit::*;
\ No newline at end of file
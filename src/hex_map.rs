@@ -0,0 +1,292 @@
+use bevy::{prelude::*, utils::HashMap};
+use crate::constants::{CLIMB_COST_PER_LEVEL, ELEVATION_STEP, LEVELS_PER_VISION_BONUS, RIVER_CROSSING_COST};
+use crate::components::Tile;
+use crate::terrain::{DepositId, TerrainId, TerrainRegistry, Yields};
+use crate::utils::{Hex, HexEdge};
+
+/// Per-hex data stored in the [`HexMap`]
+#[derive(Debug, Clone)]
+pub struct HexCell {
+    /// The tile entity spawned for this hex
+    pub entity: Entity,
+    pub terrain: TerrainId,
+    /// Height above sea level; negative under water
+    pub elevation: f32,
+    /// Resource deposit on this hex, if any
+    pub deposit: Option<DepositId>,
+    /// What the hex produces each turn
+    pub yields: Yields,
+    /// Units (and later buildings) currently standing on this hex
+    pub occupants: Vec<Entity>,
+}
+
+impl HexCell {
+    pub fn new(entity: Entity, terrain: TerrainId, elevation: f32) -> Self {
+        Self {
+            entity,
+            terrain,
+            elevation,
+            deposit: None,
+            yields: Yields::default(),
+            occupants: Vec::new(),
+        }
+    }
+
+    /// Cell for the tile entity `entity`, copying the tile's data
+    pub fn from_tile(entity: Entity, tile: &Tile) -> Self {
+        Self {
+            deposit: tile.deposit,
+            yields: tile.yields,
+            ..Self::new(entity, tile.terrain, tile.elevation)
+        }
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        !self.occupants.is_empty()
+    }
+}
+
+/// Dense lookup from hex coordinate to tile data.
+///
+/// Cells live in a flat `Vec` covering the bounding box of the map in axial
+/// space, so lookups are a bounds check and an index. Hexes inside the box
+/// but outside the map shape are simply empty. Rivers run between tiles, so
+/// they are kept per [`HexEdge`] rather than per cell.
+#[derive(Resource, Debug, Default)]
+pub struct HexMap {
+    min: Hex,
+    width: i32,
+    height: i32,
+    cells: Vec<Option<HexCell>>,
+    /// Hex of each tile entity, so despawned tiles can be found without a scan
+    tile_positions: HashMap<Entity, Hex>,
+    occupant_positions: HashMap<Entity, Hex>,
+    /// Number of rivers flowing along each edge
+    rivers: HashMap<HexEdge, u32>,
+}
+
+impl HexMap {
+    /// Create an empty map whose storage covers all of `hexes`
+    pub fn from_hexes<'a>(hexes: impl IntoIterator<Item = &'a Hex>) -> Self {
+        let mut min = Hex::new(i32::MAX, i32::MAX);
+        let mut max = Hex::new(i32::MIN, i32::MIN);
+        for hex in hexes {
+            min = Hex::new(min.q.min(hex.q), min.r.min(hex.r));
+            max = Hex::new(max.q.max(hex.q), max.r.max(hex.r));
+        }
+
+        if min.q > max.q {
+            return Self::default();
+        }
+
+        let width = max.q - min.q + 1;
+        let height = max.r - min.r + 1;
+        Self {
+            min,
+            width,
+            height,
+            cells: vec![None; (width * height) as usize],
+            tile_positions: HashMap::new(),
+            occupant_positions: HashMap::new(),
+            rivers: HashMap::new(),
+        }
+    }
+
+    fn index(&self, hex: Hex) -> Option<usize> {
+        let q = hex.q - self.min.q;
+        let r = hex.r - self.min.r;
+        if q < 0 || r < 0 || q >= self.width || r >= self.height {
+            return None;
+        }
+        Some((r * self.width + q) as usize)
+    }
+
+    /// Store a cell, returning the one it replaced.
+    ///
+    /// Panics if `hex` lies outside the storage this map was created with.
+    pub fn insert(&mut self, hex: Hex, cell: HexCell) -> Option<HexCell> {
+        let index = self
+            .index(hex)
+            .unwrap_or_else(|| panic!("hex {} is outside the map storage", hex));
+        let entity = cell.entity;
+        let replaced = self.cells[index].replace(cell);
+        if let Some(replaced) = &replaced {
+            self.tile_positions.remove(&replaced.entity);
+        }
+        self.tile_positions.insert(entity, hex);
+        replaced
+    }
+
+    /// Remove the cell at `hex`, e.g. when its tile is despawned
+    pub fn remove(&mut self, hex: Hex) -> Option<HexCell> {
+        let index = self.index(hex)?;
+        let cell = self.cells[index].take()?;
+        self.tile_positions.remove(&cell.entity);
+        for occupant in &cell.occupants {
+            self.occupant_positions.remove(occupant);
+        }
+        Some(cell)
+    }
+
+    pub fn get(&self, hex: Hex) -> Option<&HexCell> {
+        self.index(hex).and_then(|index| self.cells[index].as_ref())
+    }
+
+    pub fn get_mut(&mut self, hex: Hex) -> Option<&mut HexCell> {
+        self.index(hex).and_then(|index| self.cells[index].as_mut())
+    }
+
+    /// Whether `hex` is part of the map
+    pub fn in_bounds(&self, hex: Hex) -> bool {
+        self.get(hex).is_some()
+    }
+
+    /// Number of hexes in the map
+    pub fn len(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|cell| cell.is_none())
+    }
+
+    /// All hexes in the map with their cells
+    pub fn iter(&self) -> impl Iterator<Item = (Hex, &HexCell)> {
+        let (min, width) = (self.min, self.width);
+        self.cells.iter().enumerate().filter_map(move |(index, cell)| {
+            let index = index as i32;
+            let hex = Hex::new(min.q + index % width, min.r + index / width);
+            cell.as_ref().map(|cell| (hex, cell))
+        })
+    }
+
+    /// Neighbours of `hex` that are part of the map
+    pub fn neighbors(&self, hex: Hex) -> impl Iterator<Item = (Hex, &HexCell)> {
+        hex.neighbors()
+            .into_iter()
+            .filter_map(move |neighbor| self.get(neighbor).map(|cell| (neighbor, cell)))
+    }
+
+    /// Tile entity at `hex`
+    pub fn tile_entity(&self, hex: Hex) -> Option<Entity> {
+        self.get(hex).map(|cell| cell.entity)
+    }
+
+    /// Units standing on `hex`
+    pub fn occupants(&self, hex: Hex) -> &[Entity] {
+        self.get(hex).map_or(&[], |cell| cell.occupants.as_slice())
+    }
+
+    /// Move `occupant` onto `hex`, removing it from wherever it stood
+    /// before. Returns false (and leaves it unplaced) if the hex is not
+    /// part of the map.
+    pub fn place_occupant(&mut self, occupant: Entity, hex: Hex) -> bool {
+        self.remove_occupant(occupant);
+        let Some(cell) = self.get_mut(hex) else {
+            return false;
+        };
+        cell.occupants.push(occupant);
+        self.occupant_positions.insert(occupant, hex);
+        true
+    }
+
+    /// Remove `occupant` from the map entirely
+    pub fn remove_occupant(&mut self, occupant: Entity) {
+        if let Some(hex) = self.occupant_positions.remove(&occupant) {
+            if let Some(cell) = self.get_mut(hex) {
+                cell.occupants.retain(|&entity| entity != occupant);
+            }
+        }
+    }
+
+    /// Whole [`ELEVATION_STEP`]s of height at `hex`; water is level 0
+    pub fn height_level(&self, hex: Hex) -> Option<i32> {
+        self.get(hex)
+            .map(|cell| (cell.elevation.max(0.0) / ELEVATION_STEP).floor() as i32)
+    }
+
+    /// Movement points needed to enter `to` from `from`: the terrain's cost
+    /// plus [`CLIMB_COST_PER_LEVEL`] for every level climbed and
+    /// [`RIVER_CROSSING_COST`] for crossing a river. `None` if `to` is off
+    /// the map or impassable.
+    pub fn movement_cost(&self, terrains: &TerrainRegistry, from: Hex, to: Hex) -> Option<u32> {
+        let cell = self.get(to)?;
+        if !terrains.is_passable(cell.terrain) {
+            return None;
+        }
+        let climb = match (self.height_level(from), self.height_level(to)) {
+            (Some(from_level), Some(to_level)) => (to_level - from_level).max(0) as u32,
+            _ => 0,
+        };
+        let river = if self.crosses_river(from, to) { RIVER_CROSSING_COST } else { 0 };
+        Some(terrains.get(cell.terrain).movement_cost + climb * CLIMB_COST_PER_LEVEL + river)
+    }
+
+    /// Extra sight range for a unit on `hex`, from its terrain and one tile
+    /// per [`LEVELS_PER_VISION_BONUS`] levels of height
+    pub fn vision_bonus(&self, terrains: &TerrainRegistry, hex: Hex) -> i32 {
+        let Some(cell) = self.get(hex) else {
+            return 0;
+        };
+        let height_bonus = self.height_level(hex).unwrap_or(0) / LEVELS_PER_VISION_BONUS;
+        terrains.get(cell.terrain).vision_modifier + height_bonus
+    }
+
+    /// Record a river flowing along `edges`
+    pub fn add_river(&mut self, edges: impl IntoIterator<Item = HexEdge>) {
+        for edge in edges {
+            *self.rivers.entry(edge).or_insert(0) += 1;
+        }
+    }
+
+    /// Number of rivers flowing along `edge`; 0 if there is none
+    pub fn river_flow(&self, edge: HexEdge) -> u32 {
+        self.rivers.get(&edge).copied().unwrap_or(0)
+    }
+
+    /// Whether moving between neighbours `a` and `b` crosses a river
+    pub fn crosses_river(&self, a: Hex, b: Hex) -> bool {
+        a.distance(b) == 1 && self.rivers.contains_key(&HexEdge::new(a, b))
+    }
+
+    /// Every river edge with its flow
+    pub fn rivers(&self) -> impl Iterator<Item = (HexEdge, u32)> + '_ {
+        self.rivers.iter().map(|(&edge, &flow)| (edge, flow))
+    }
+
+    /// Resource deposit at `hex`, if any
+    pub fn deposit(&self, hex: Hex) -> Option<DepositId> {
+        self.get(hex).and_then(|cell| cell.deposit)
+    }
+
+    /// Every hex holding a deposit, with the deposit
+    pub fn deposits(&self) -> impl Iterator<Item = (Hex, DepositId)> + '_ {
+        self.iter().filter_map(|(hex, cell)| cell.deposit.map(|deposit| (hex, deposit)))
+    }
+
+    /// Every hex holding a deposit of the given kind
+    pub fn deposits_of(&self, deposit: DepositId) -> impl Iterator<Item = Hex> + '_ {
+        self.deposits().filter(move |&(_, other)| other == deposit).map(|(hex, _)| hex)
+    }
+
+    /// What `hex` produces each turn; nothing if it is off the map
+    pub fn yields(&self, hex: Hex) -> Yields {
+        self.get(hex).map_or(Yields::default(), |cell| cell.yields)
+    }
+
+    /// Combined yields of `hexes`, e.g. the tiles a city works; hexes off
+    /// the map add nothing
+    pub fn total_yields(&self, hexes: impl IntoIterator<Item = Hex>) -> Yields {
+        hexes.into_iter().map(|hex| self.yields(hex)).sum()
+    }
+
+    /// Hex whose cell belongs to the tile entity `entity`
+    pub fn tile_position(&self, entity: Entity) -> Option<Hex> {
+        self.tile_positions.get(&entity).copied()
+    }
+
+    /// Hex an occupant was last placed on
+    pub fn occupant_position(&self, occupant: Entity) -> Option<Hex> {
+        self.occupant_positions.get(&occupant).copied()
+    }
+}
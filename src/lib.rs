@@ -6,4 +6,7 @@ pub mod systems;
 pub mod utils;
 pub mod traits;
 pub mod constants;
+pub mod hex_map;
+pub mod pathfinding;
+pub mod terrain;
 pub mod ui;
\ No newline at end of file
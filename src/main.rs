@@ -4,6 +4,10 @@ mod systems;
 mod utils;
 mod traits;
 mod constants;
+mod hex_map;
+mod pathfinding;
+mod ui;
+mod terrain;
 pub mod units;
 
 use bevy::prelude::*;
@@ -13,8 +17,48 @@ use resources::*;
 use systems::*;
 pub use traits::*;
 use constants::*;
+use utils::{HexLayout, HexOrientation};
+use hex_map::HexMap;
+use terrain::TerrainPlugin;
 
 fn main() {
+    // Pass `--seed <n>` to regenerate the map from a bug report,
+    // `--preset <name>` (e.g. "archipelago") to pick a map type,
+    // `--players <n>` to set how many players start on it and
+    // `--orientation flat` for flat-top hexes
+    let arg_value = |flag: &str| std::env::args().skip_while(|arg| arg != flag).nth(1);
+    let seed = arg_value("--seed")
+        .map(|seed| {
+            seed.parse().unwrap_or_else(|_| {
+                eprintln!("Invalid seed {:?}, using a random one", seed);
+                rand::random()
+            })
+        })
+        .unwrap_or_else(rand::random);
+    let preset = arg_value("--preset")
+        .map(|name| {
+            MapPreset::from_name(&name).unwrap_or_else(|| {
+                eprintln!("Unknown map preset {:?}, using {}", name, MapPreset::default().name());
+                MapPreset::default()
+            })
+        })
+        .unwrap_or_default();
+    let mut map_gen_config = MapGenConfig::for_preset(preset, seed);
+    if let Some(players) = arg_value("--players") {
+        match players.parse() {
+            Ok(players) => map_gen_config.start.players = players,
+            Err(_) => eprintln!("Invalid player count {:?}, using {}", players, map_gen_config.start.players),
+        }
+    }
+    let orientation = arg_value("--orientation")
+        .map(|name| {
+            HexOrientation::from_name(&name).unwrap_or_else(|| {
+                eprintln!("Unknown hex orientation {:?}, using pointy", name);
+                HexOrientation::POINTY
+            })
+        })
+        .unwrap_or(HexOrientation::POINTY);
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -25,17 +69,29 @@ fn main() {
             ..default()
         }))
         .add_plugins(SelectionPlugin)  // Add the selection plugin
+        .add_plugins(ui::MapInfoPlugin)  // Map seed and tile tooltip, without the menus
+        .add_plugins(TerrainPlugin)     // Loads terrain definitions, then their textures
+        .add_plugins(TerrainAtlasPlugin)  // Packs terrain textures, then enters Playing
+        .add_state::<GameState>()
         .init_resource::<CameraConfig>()
+        .insert_resource(HexLayout::new(orientation, HEX_SIZE))
+        .insert_resource(map_gen_config)
+        .init_resource::<HexMap>()
+        .init_resource::<StartPositions>()
+        .init_resource::<TileChunks>()
+        .init_resource::<GridOverlay>()
         .init_resource::<ContextMenuState>()  // Still needed for the new context menu
         .init_resource::<DetailedMenuState>()
         .init_resource::<SelectionState>()  // Initialize SelectionState
         .init_resource::<MouseState>()      // Initialize MouseState
         .add_event::<UnitCommand>()
-        .add_systems(Startup, (
-            setup_camera,
+        .add_systems(Startup, setup_camera)
+        // The map needs terrain definitions and textures, so wait for them;
+        // units are then placed on the generated map
+        .add_systems(OnEnter(GameState::Playing), (
             draw_hex_grid,
             entity_startup_system,
-        ))
+        ).chain())
         .add_systems(Update, (
             // Camera systems
             (
@@ -47,10 +103,22 @@ fn main() {
             ),
             // Post-selection systems (selection is handled by SelectionPlugin)
             (
-                hex_sprite_system,
                 entity_movement_system,
-                unit_command_system
+                unit_command_system,
+                sync_hex_map_tiles_system,
+                sync_unit_occupancy_system,
             ),
+            // Tile rendering
+            (
+                mark_dirty_chunks_system,
+                rebuild_tile_chunks_system,
+            ).chain(),
+            // Debug overlays, toggled from the keyboard
+            (
+                toggle_grid_overlay_system,
+                update_grid_lines_system,
+                update_coordinate_labels_system,
+            ).chain(),
         ))
         
         .run();
@@ -0,0 +1,294 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use bevy::prelude::Entity;
+use bevy::utils::HashMap;
+use crate::hex_map::HexMap;
+use crate::terrain::TerrainRegistry;
+use crate::utils::Hex;
+
+/// Route between two hexes, found by [`find_path`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexPath {
+    /// Every hex along the way, start first and goal last
+    pub hexes: Vec<Hex>,
+    /// Movement points paid entering each hex after the start, so
+    /// `step_costs[i]` belongs to `hexes[i + 1]`
+    pub step_costs: Vec<u32>,
+}
+
+impl HexPath {
+    pub fn start(&self) -> Hex {
+        self.hexes[0]
+    }
+
+    pub fn goal(&self) -> Hex {
+        self.hexes[self.hexes.len() - 1]
+    }
+
+    /// Number of steps, not counting the start
+    pub fn len(&self) -> usize {
+        self.step_costs.len()
+    }
+
+    /// Whether the path starts on its goal
+    pub fn is_empty(&self) -> bool {
+        self.step_costs.is_empty()
+    }
+
+    /// Movement points needed to walk the whole path
+    pub fn total_cost(&self) -> u32 {
+        self.step_costs.iter().sum()
+    }
+
+    /// The hexes after the start, each with the movement points spent
+    /// reaching it
+    pub fn steps(&self) -> impl Iterator<Item = (Hex, u32)> + '_ {
+        self.hexes[1..].iter()
+            .zip(&self.step_costs)
+            .scan(0, |spent, (&hex, &cost)| {
+                *spent += cost;
+                Some((hex, *spent))
+            })
+    }
+
+    /// Turn on which each step after the start is reached by a unit with
+    /// `movement` points a turn, counting the current turn as 1. A turn
+    /// ends when the next step costs more than is left of it; a step
+    /// costing more than a whole turn takes a turn of its own.
+    pub fn turns(&self, movement: u32) -> Vec<u32> {
+        let mut turn = 1;
+        let mut left = movement;
+        self.step_costs.iter()
+            .map(|&cost| {
+                if cost > left && left < movement {
+                    turn += 1;
+                    left = movement;
+                }
+                left = left.saturating_sub(cost);
+                turn
+            })
+            .collect()
+    }
+
+    /// The part of the path that can be walked with `budget` movement points
+    /// and ends on a hex `can_stop` allows
+    pub fn truncated(&self, budget: u32, can_stop: impl Fn(Hex) -> bool) -> HexPath {
+        let affordable = self.steps().take_while(|&(_, spent)| spent <= budget).count();
+        let steps = (1..=affordable).rev()
+            .find(|&steps| can_stop(self.hexes[steps]))
+            .unwrap_or(0);
+        HexPath {
+            hexes: self.hexes[..=steps].to_vec(),
+            step_costs: self.step_costs[..steps].to_vec(),
+        }
+    }
+}
+
+/// Cheapest path from `start` to `goal` by A*, or `None` if the goal can't
+/// be reached.
+///
+/// `step_cost` gives the movement points needed to step between two
+/// neighbouring hexes, or `None` if the step isn't allowed, e.g. off the
+/// map or into an impassable or occupied hex. Every step is charged at
+/// least 1 so hex distance stays a safe estimate of the cost left.
+pub fn find_path(start: Hex, goal: Hex, mut step_cost: impl FnMut(Hex, Hex) -> Option<u32>) -> Option<HexPath> {
+    // Cheapest known cost to each hex, and the hex it was reached from
+    let mut best: HashMap<Hex, (u32, Option<Hex>)> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    best.insert(start, (0, None));
+    frontier.push(Reverse((start.distance(goal) as u32, 0, start)));
+
+    while let Some(Reverse((_, cost, hex))) = frontier.pop() {
+        if hex == goal {
+            return Some(trace_path(&best, goal));
+        }
+        if best.get(&hex).is_some_and(|&(known, _)| cost > known) {
+            // Already reached more cheaply
+            continue;
+        }
+
+        for neighbor in hex.neighbors() {
+            let Some(step) = step_cost(hex, neighbor) else {
+                continue;
+            };
+            let next_cost = cost + step.max(1);
+            if best.get(&neighbor).is_some_and(|&(known, _)| known <= next_cost) {
+                continue;
+            }
+            best.insert(neighbor, (next_cost, Some(hex)));
+            frontier.push(Reverse((next_cost + neighbor.distance(goal) as u32, next_cost, neighbor)));
+        }
+    }
+
+    None
+}
+
+/// Every hex that can be reached from `start` with at most `budget`
+/// movement points, with the cheapest cost of getting there; `start`
+/// itself costs nothing. Steps are charged as in [`find_path`].
+pub fn reachable(start: Hex, budget: u32, mut step_cost: impl FnMut(Hex, Hex) -> Option<u32>) -> HashMap<Hex, u32> {
+    let mut costs = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    costs.insert(start, 0);
+    frontier.push(Reverse((0, start)));
+
+    while let Some(Reverse((cost, hex))) = frontier.pop() {
+        if costs.get(&hex).is_some_and(|&known| cost > known) {
+            continue;
+        }
+
+        for neighbor in hex.neighbors() {
+            let Some(step) = step_cost(hex, neighbor) else {
+                continue;
+            };
+            let next_cost = cost + step.max(1);
+            if next_cost > budget || costs.get(&neighbor).is_some_and(|&known| known <= next_cost) {
+                continue;
+            }
+            costs.insert(neighbor, next_cost);
+            frontier.push(Reverse((next_cost, neighbor)));
+        }
+    }
+
+    costs
+}
+
+/// Walk back from `goal` through the hexes each was reached from
+fn trace_path(best: &HashMap<Hex, (u32, Option<Hex>)>, goal: Hex) -> HexPath {
+    let mut hexes = vec![goal];
+    let mut step_costs = Vec::new();
+    let mut hex = goal;
+    while let Some(&(cost, Some(previous))) = best.get(&hex) {
+        step_costs.push(cost - best[&previous].0);
+        hexes.push(previous);
+        hex = previous;
+    }
+    hexes.reverse();
+    step_costs.reverse();
+    HexPath { hexes, step_costs }
+}
+
+/// Step costs for `unit` walking the map: terrain, climbing and rivers as in
+/// [`HexMap::movement_cost`]. Hexes held by units that `is_ally` rejects
+/// can't be entered; allied units can be walked through but not stopped on,
+/// see [`HexPath::truncated`].
+pub fn unit_step_cost<'a>(
+    hex_map: &'a HexMap,
+    terrains: &'a TerrainRegistry,
+    unit: Entity,
+    is_ally: impl Fn(Entity) -> bool + 'a,
+) -> impl Fn(Hex, Hex) -> Option<u32> + 'a {
+    move |from, to| {
+        let blocked = hex_map.occupants(to).iter().any(|&other| other != unit && !is_ally(other));
+        if blocked {
+            return None;
+        }
+        hex_map.movement_cost(terrains, from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex_map::HexCell;
+    use crate::terrain::SHIPPED_DEFINITIONS;
+    use crate::utils::spiral;
+
+    /// Step costs on a map of `radius` around the origin, with `cost` giving
+    /// the price of entering each hex
+    fn bounded(radius: i32, cost: impl Fn(Hex) -> Option<u32>) -> impl FnMut(Hex, Hex) -> Option<u32> {
+        move |_, to| if to.length() <= radius { cost(to) } else { None }
+    }
+
+    #[test]
+    fn find_path_prefers_cheap_detours_over_short_costly_paths() {
+        // The only 4-step path runs along r = 0, through a swamp
+        let start = Hex::new(-2, 0);
+        let goal = Hex::new(2, 0);
+        let swamp = |hex: Hex| hex.r == 0 && hex != start && hex != goal;
+        let path = find_path(start, goal, bounded(4, |hex| Some(if swamp(hex) { 10 } else { 1 }))).unwrap();
+
+        assert_eq!(path.start(), start);
+        assert_eq!(path.goal(), goal);
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.total_cost(), 5);
+        assert!(!path.hexes.iter().any(|&hex| swamp(hex)));
+        for pair in path.hexes.windows(2) {
+            assert_eq!(pair[0].distance(pair[1]), 1);
+        }
+    }
+
+    #[test]
+    fn find_path_gives_none_for_unreachable_goals() {
+        let goal = Hex::new(3, 0);
+        let walled_in = |hex: Hex| hex.distance(goal) == 1;
+        let step_cost = bounded(5, |hex| (!walled_in(hex)).then_some(1));
+
+        assert_eq!(find_path(Hex::ZERO, goal, step_cost), None);
+    }
+
+    #[test]
+    fn allies_can_be_walked_through_but_not_stopped_on() {
+        let registry = TerrainRegistry::from_ron(SHIPPED_DEFINITIONS).unwrap();
+        let grass = registry.id("grass").unwrap();
+        let hexes: Vec<Hex> = spiral(Hex::ZERO, 3).collect();
+        let mut hex_map = HexMap::from_hexes(&hexes);
+        for (index, &hex) in hexes.iter().enumerate() {
+            hex_map.insert(hex, HexCell::new(Entity::from_raw(index as u32), grass, 0.0));
+        }
+        let unit = Entity::from_raw(100);
+        let ally = Entity::from_raw(101);
+        let enemy = Entity::from_raw(102);
+        hex_map.place_occupant(unit, Hex::ZERO);
+        hex_map.place_occupant(ally, Hex::new(1, 0));
+        hex_map.place_occupant(enemy, Hex::new(0, 1));
+        let step_cost = || unit_step_cost(&hex_map, &registry, unit, move |other| other == ally);
+        let can_stop = |hex: Hex| hex_map.occupants(hex).is_empty();
+
+        let path = find_path(Hex::ZERO, Hex::new(2, 0), step_cost()).unwrap();
+        assert_eq!(path.hexes, vec![Hex::ZERO, Hex::new(1, 0), Hex::new(2, 0)]);
+        assert_eq!(path.truncated(1, can_stop).len(), 0);
+        assert_eq!(path.truncated(2, can_stop).goal(), Hex::new(2, 0));
+        assert_eq!(find_path(Hex::ZERO, Hex::new(0, 1), step_cost()), None);
+    }
+
+    #[test]
+    fn reachable_keeps_within_budget_at_the_cheapest_cost() {
+        let swamp = Hex::new(1, 0);
+        let costs = reachable(Hex::ZERO, 3, bounded(5, |hex| Some(if hex == swamp { 5 } else { 1 })));
+
+        assert_eq!(costs[&Hex::ZERO], 0);
+        assert_eq!(costs[&Hex::new(-3, 0)], 3);
+        // Round the swamp rather than through it
+        assert_eq!(costs[&Hex::new(2, 0)], 3);
+        assert!(!costs.contains_key(&swamp));
+        assert!(!costs.contains_key(&Hex::new(-4, 0)));
+        assert!(costs.values().all(|&cost| cost <= 3));
+    }
+
+    #[test]
+    fn reachable_skips_blocked_hexes() {
+        let wall = |hex: Hex| hex.q == 1;
+        let costs = reachable(Hex::ZERO, 4, bounded(5, |hex| (!wall(hex)).then_some(1)));
+
+        assert!(costs.keys().all(|&hex| hex.q < 1));
+        assert_eq!(costs.len(), spiral(Hex::ZERO, 4).filter(|hex| hex.q < 1).count());
+    }
+
+    #[test]
+    fn turns_start_a_new_turn_when_a_step_costs_more_than_is_left() {
+        let path = HexPath {
+            hexes: vec![Hex::ZERO; 5],
+            step_costs: vec![1, 3, 1, 1],
+        };
+        // The 3 doesn't fit after the first step, and takes all of turn 2
+        assert_eq!(path.turns(2), vec![1, 2, 3, 3]);
+
+        let path = HexPath {
+            hexes: vec![Hex::ZERO; 3],
+            step_costs: vec![3, 1],
+        };
+        // A fresh turn always makes its first step, however costly
+        assert_eq!(path.turns(2), vec![1, 2]);
+    }
+}
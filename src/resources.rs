@@ -3,7 +3,18 @@ use bevy::{prelude::*, utils::HashMap};
 
 use crate::components::*;
 use crate::constants::*;
-use crate::utils::axial_to_world;
+use crate::utils::Hex;
+
+/// Top-level game flow
+#[derive(States, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    /// Waiting for data assets such as terrain definitions
+    #[default]
+    Loading,
+    /// Packing the terrain textures into an atlas
+    LoadingTextures,
+    Playing,
+}
 
 #[derive(Resource)]
 pub struct MouseState {
@@ -71,11 +82,9 @@ impl Default for MouseState {
 
 
 
-#[derive(Resource)]
-pub struct TerrainTextureSet {
-    pub terrains: Vec<Terrain>,
-    pub texture_variants: HashMap<String, Vec<Handle<Image>>>,
-}
+/// Start hex of each player, chosen by map generation
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StartPositions(pub Vec<Hex>);
 
 #[derive(Resource, Clone)]
 pub struct UnitTextureSet {
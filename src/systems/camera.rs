@@ -4,6 +4,8 @@ use bevy::render::camera::OrthographicProjection;
 use bevy::input::mouse::MouseWheel;
 use crate::resources::MouseState;
 use crate::constants::input::*;
+use crate::constants::TERRAIN_Z;
+use crate::utils::{Hex, HexLayout};
 
 use bevy::ui::Style;
 
@@ -60,6 +62,32 @@ pub fn setup_camera(
     ))
     .insert(Name::new("Main Camera"));
 }
+/// Point on the terrain plane under a cursor position.
+///
+/// Casts a ray through the main camera's projection, rotation and mirrored
+/// y scale and intersects it with the terrain plane, so the result stays
+/// correct while the camera is tilted for the isometric view.
+pub fn cursor_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cursor_position: Vec2,
+) -> Option<Vec3> {
+    let ray = camera.viewport_to_world(camera_transform, cursor_position)?;
+    let distance = ray.intersect_plane(Vec3::new(0.0, 0.0, TERRAIN_Z), Vec3::Z)?;
+    Some(ray.get_point(distance))
+}
+
+/// Hex under a cursor position, see [`cursor_to_world`]
+pub fn cursor_to_hex(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    layout: &HexLayout,
+    cursor_position: Vec2,
+) -> Option<Hex> {
+    cursor_to_world(camera, camera_transform, cursor_position)
+        .map(|position| layout.world_to_hex(position))
+}
+
 pub fn camera_movement_system(
     keyboard_input: Res<Input<KeyCode>>,
     camera_config: Res<CameraConfig>,
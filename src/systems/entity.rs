@@ -1,10 +1,14 @@
-use bevy::{prelude::*, utils::HashMap};
-use crate::components::{Unit, UnitType, AnimationData, AnimationSet, AnimationState, EntityType, GameEntity, Selectable, SelectableType, UnitAnimation, UnitState};
-use crate::constants::UNIT_Z;
+use std::collections::VecDeque;
+use bevy::{ecs::system::SystemParam, prelude::*, utils::{HashMap, HashSet}};
+use crate::components::{Unit, UnitType, AnimationData, AnimationSet, AnimationState, EntityType, GameEntity, Owner, Selectable, SelectableType, UnitAnimation, UnitState};
+use crate::constants::{STARTING_UNITS, UNIT_Z};
+use crate::hex_map::HexMap;
+use crate::pathfinding::{find_path, reachable, unit_step_cost, HexPath};
+use crate::terrain::TerrainRegistry;
 use crate::units::*;
-use crate::resources::UnitTextureSet;
+use crate::resources::{StartPositions, UnitTextureSet};
 use crate::traits::Moveable;
-use crate::utils::coordinates::axial_to_world;
+use crate::utils::{Hex, HexLayout};
 
 use bevy::ui::Style;
 
@@ -12,12 +16,15 @@ use bevy::ui::Style;
 const DEFAULT_UNIT_HEALTH: f32 = 100.0;
 const DEFAULT_MOVEMENT_RANGE: i32 = 2;
 const DEFAULT_ATTACK_DAMAGE: f32 = 10.0;
+/// How fast units walk between hexes, in world units per second
+const UNIT_MOVE_SPEED: f32 = 150.0;
 
 impl Default for UnitState {
     fn default() -> Self {
         Self {
             is_moving: false,
             target_position: None,
+            path: Vec::new(),
         }
     }
 }
@@ -25,6 +32,10 @@ impl Default for UnitState {
 pub fn entity_startup_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    layout: Res<HexLayout>,
+    hex_map: Res<HexMap>,
+    registry: Res<TerrainRegistry>,
+    start_positions: Res<StartPositions>,
 ) {
     let mut animation_sets = HashMap::new();
 
@@ -58,30 +69,71 @@ pub fn entity_startup_system(
     };
     commands.insert_resource(unit_textures.clone());
 
-    // Spawn initial units
-    let initial_units = vec![
-        (UnitType::Warrior, 0, 0),
-        (UnitType::Archer, 1, 1),
-        (UnitType::ElvenArcher, 1, 0),
-    ];
-
-    for (unit_type, q, r) in initial_units {
-        match unit_type {
-            UnitType::Warrior => spawn_unit::<Warrior>(&mut commands, &unit_textures, unit_type, q, r),
-            UnitType::Archer => spawn_unit::<Archer>(&mut commands, &unit_textures, unit_type, q, r),
-            UnitType::ElvenArcher => spawn_unit::<ElvenArcher>(&mut commands, &unit_textures, unit_type, q, r),
-            UnitType::Mage => todo!("Implement Mage spawning"),
-        };
+    // Spawn each player's units on their start hex and the closest free
+    // hexes around it, so no unit starts on water, a mountain or another unit
+    let mut taken = HashSet::new();
+    for (player, &start) in start_positions.0.iter().enumerate() {
+        let positions = free_hexes_near(&hex_map, &registry, start, STARTING_UNITS.len(), &mut taken);
+        if positions.len() < STARTING_UNITS.len() {
+            warn!(
+                "Player {} has room for only {} of {} starting units",
+                player + 1, positions.len(), STARTING_UNITS.len(),
+            );
+        }
+
+        for (unit_type, position) in STARTING_UNITS.iter().cloned().zip(positions) {
+            let entity = match unit_type {
+                UnitType::Warrior => spawn_unit::<Warrior>(&mut commands, &unit_textures, &layout, unit_type, position),
+                UnitType::Archer => spawn_unit::<Archer>(&mut commands, &unit_textures, &layout, unit_type, position),
+                UnitType::ElvenArcher => spawn_unit::<ElvenArcher>(&mut commands, &unit_textures, &layout, unit_type, position),
+                UnitType::Mage => todo!("Implement Mage spawning"),
+            };
+            commands.entity(entity).insert(Owner(player));
+        }
     }
 }
+
+/// Up to `count` passable hexes outside `taken`, closest to `start` first
+/// and reachable from it over passable terrain; they are added to `taken`.
+/// Empty if `start` itself is impassable.
+fn free_hexes_near(
+    hex_map: &HexMap,
+    registry: &TerrainRegistry,
+    start: Hex,
+    count: usize,
+    taken: &mut HashSet<Hex>,
+) -> Vec<Hex> {
+    let passable = |hex: Hex| hex_map.get(hex).is_some_and(|cell| registry.is_passable(cell.terrain));
+    let mut found = Vec::new();
+    if !passable(start) {
+        return found;
+    }
+
+    let mut visited: HashSet<Hex> = [start].into_iter().collect();
+    let mut frontier = VecDeque::from([start]);
+    while let Some(hex) = frontier.pop_front() {
+        if found.len() == count {
+            break;
+        }
+        if taken.insert(hex) {
+            found.push(hex);
+        }
+        for neighbor in hex.neighbors() {
+            if passable(neighbor) && visited.insert(neighbor) {
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+    found
+}
 // Keep the spawn_unit function separate for reuse
 // In entity.rs
 pub fn spawn_unit<T: UnitBehavior>(
     commands: &mut Commands,
     unit_textures: &UnitTextureSet,
+    layout: &HexLayout,
     unit_type: UnitType,
-    q: i32,
-    r: i32,
+    position: Hex,
 ) -> Entity {
     if let Some(animations) = unit_textures.animation_sets.get(unit_type.name()) {
         let default_texture = animations.idle.as_ref()
@@ -94,8 +146,7 @@ pub fn spawn_unit<T: UnitBehavior>(
         let base_unit = BaseUnit::new(
             unit_type.name().to_string(),
             default_texture.clone(),
-            q,
-            r,
+            position,
         );
 
         let unit = T::new(base_unit);
@@ -106,7 +157,7 @@ pub fn spawn_unit<T: UnitBehavior>(
                 name: unit_type.name().to_string(),
                 description: "A unit".to_string(),
                 entity_type: EntityType::Unit,
-                position: (q, r),
+                position,
                 sprite: default_texture.clone(),
             },
             health: DEFAULT_UNIT_HEALTH,
@@ -121,8 +172,8 @@ pub fn spawn_unit<T: UnitBehavior>(
             SpriteBundle {
                 texture: default_texture,
                 transform: Transform::from_translation(Vec3::new(
-                    axial_to_world(q, r).x,
-                    axial_to_world(q, r).y,
+                    layout.hex_to_world(position).x,
+                    layout.hex_to_world(position).y,
                     UNIT_Z
                 )).with_scale(T::get_scale()),
                 ..default()
@@ -148,35 +199,64 @@ pub struct UnitBundle {
 }
 
 
+/// Walk units along their paths one hex at a time
 pub fn entity_movement_system(
     mut query: Query<(&mut Unit, &mut Transform, &mut UnitState)>,
+    layout: Res<HexLayout>,
     time: Res<Time>,
 ) {
     for (mut unit, mut transform, mut state) in query.iter_mut() {
-        if let Some(target) = state.target_position {
-            if unit.can_move_to(target.0, target.1) {
-                if unit.move_to(target.0, target.1) {
-                    transform.translation = axial_to_world(target.0, target.1);
-                    state.target_position = None;
-                    state.is_moving = false;
-                }
-            } else {
+        let Some(&next) = state.path.first() else {
+            if state.is_moving {
                 state.target_position = None;
                 state.is_moving = false;
             }
+            continue;
+        };
+
+        let destination = layout.hex_to_world(next).truncate().extend(transform.translation.z);
+        let remaining = destination - transform.translation;
+        let step = UNIT_MOVE_SPEED * time.delta_seconds();
+        if remaining.length() > step {
+            transform.translation += remaining.normalize() * step;
+            continue;
+        }
+
+        transform.translation = destination;
+        if unit.move_to(next) {
+            state.path.remove(0);
+        } else {
+            state.path.clear();
         }
     }
 }
 
+/// Keep unit occupancy in the [`HexMap`] in step with unit positions
+pub fn sync_unit_occupancy_system(
+    units: Query<(Entity, &Unit), Changed<Unit>>,
+    mut removed_units: RemovedComponents<Unit>,
+    mut hex_map: ResMut<HexMap>,
+) {
+    for (entity, unit) in units.iter() {
+        if hex_map.occupant_position(entity) != Some(unit.position()) {
+            hex_map.place_occupant(entity, unit.position());
+        }
+    }
+
+    for entity in removed_units.iter() {
+        hex_map.remove_occupant(entity);
+    }
+}
+
 // Implementation for Unit
 impl Unit {
-    pub fn new(name: String, sprite: Handle<Image>, q: i32, r: i32) -> Self {
+    pub fn new(name: String, sprite: Handle<Image>, position: Hex) -> Self {
         Self {
             entity: GameEntity {
                 name,
                 description: "A unit".to_string(),
                 entity_type: EntityType::Unit,
-                position: (q, r),
+                position,
                 sprite,
             },
             health: DEFAULT_UNIT_HEALTH,
@@ -186,7 +266,7 @@ impl Unit {
     }
 
     #[inline]
-    pub fn position(&self) -> (i32, i32) {
+    pub fn position(&self) -> Hex {
         self.entity.position
     }
 
@@ -202,15 +282,13 @@ impl Unit {
 }
 
 impl Moveable for Unit {
-    fn can_move_to(&self, q: i32, r: i32) -> bool {
-        let (current_q, current_r) = self.entity.position;
-        let distance = calculate_hex_distance(current_q, current_r, q, r);
-        distance <= self.movement_range
+    fn can_move_to(&self, target: Hex) -> bool {
+        self.entity.position.distance(target) <= self.movement_range
     }
 
-    fn move_to(&mut self, q: i32, r: i32) -> bool {
-        if self.can_move_to(q, r) {
-            self.entity.position = (q, r);
+    fn move_to(&mut self, target: Hex) -> bool {
+        if self.can_move_to(target) {
+            self.entity.position = target;
             true
         } else {
             false
@@ -218,24 +296,81 @@ impl Moveable for Unit {
     }
 }
 
-#[inline]
-fn calculate_hex_distance(q1: i32, r1: i32, q2: i32, r2: i32) -> i32 {
-    ((q1 - q2).abs() + (r1 - r2).abs() + (q1 + r1 - q2 - r2).abs()) / 2
+/// Where units can go, for systems that move units or show their options
+#[derive(SystemParam)]
+pub struct UnitMovement<'w, 's> {
+    hex_map: Res<'w, HexMap>,
+    terrains: Option<Res<'w, TerrainRegistry>>,
+    units: Query<'w, 's, (&'static Unit, Option<&'static Owner>)>,
+}
+
+impl UnitMovement<'_, '_> {
+    /// Cheapest path for `unit` to `target`, through hexes held by its own
+    /// player's units but around everyone else's
+    pub fn path(&self, unit: Entity, target: Hex) -> Option<HexPath> {
+        let terrains = self.terrains.as_deref()?;
+        let (mover, _) = self.units.get(unit).ok()?;
+        find_path(mover.position(), target, unit_step_cost(&self.hex_map, terrains, unit, self.ally_of(unit)))
+    }
+
+    /// Movement points `unit` has each turn
+    pub fn movement_points(&self, unit: Entity) -> Option<u32> {
+        self.units.get(unit).ok().map(|(mover, _)| mover.movement_range.max(0) as u32)
+    }
+
+    /// Every hex `unit` can end a move on this turn, with the movement
+    /// points it costs to get there; its own hex costs nothing
+    pub fn reachable(&self, unit: Entity) -> HashMap<Hex, u32> {
+        let (Some(terrains), Ok((mover, _))) = (self.terrains.as_deref(), self.units.get(unit)) else {
+            return HashMap::new();
+        };
+        let mut costs = reachable(
+            mover.position(),
+            mover.movement_range.max(0) as u32,
+            unit_step_cost(&self.hex_map, terrains, unit, self.ally_of(unit)),
+        );
+        costs.retain(|&hex, _| self.can_stop(unit, hex));
+        costs
+    }
+
+    /// Whether `unit` may end a move on `hex`, i.e. no other unit holds it
+    pub fn can_stop(&self, unit: Entity, hex: Hex) -> bool {
+        self.hex_map.occupants(hex).iter().all(|&other| other == unit)
+    }
+
+    fn ally_of(&self, unit: Entity) -> impl Fn(Entity) -> bool + '_ {
+        let owner = self.units.get(unit).ok().and_then(|(_, owner)| owner.copied());
+        move |other| {
+            owner.is_some() && self.units.get(other).ok().and_then(|(_, owner)| owner.copied()) == owner
+        }
+    }
 }
 
-// Optional: Add a system to handle unit commands
+/// Resolve unit commands; a move finds the cheapest path to its target and
+/// walks as much of it as the unit's movement range covers
 pub fn unit_command_system(
     mut commands: EventReader<UnitCommand>,
-    mut query: Query<(&mut UnitState, &Unit)>,
+    mut states: Query<&mut UnitState>,
+    movement: UnitMovement,
 ) {
     for command in commands.iter() {
-        if let Ok((mut state, unit)) = query.get_mut(command.unit) {
+        if let Ok(mut state) = states.get_mut(command.unit) {
             match command.command_type {
-                UnitCommandType::MoveTo(q, r) => {
-                    if unit.can_move_to(q, r) {
-                        state.target_position = Some((q, r));
-                        state.is_moving = true;
+                UnitCommandType::MoveTo(target) => {
+                    let Some(path) = movement.path(command.unit, target) else {
+                        continue;
+                    };
+                    let budget = movement.movement_points(command.unit).unwrap_or(0);
+
+                    // Stop where this turn's movement runs out, on a hex no
+                    // other unit holds
+                    let path = path.truncated(budget, |hex| movement.can_stop(command.unit, hex));
+                    if path.is_empty() {
+                        continue;
                     }
+                    state.target_position = Some(path.goal());
+                    state.path = path.hexes[1..].to_vec();
+                    state.is_moving = true;
                 }
                 // Add other command types as needed
             }
@@ -251,7 +386,7 @@ pub struct UnitCommand {
 }
 
 pub enum UnitCommandType {
-    MoveTo(i32, i32),
+    MoveTo(Hex),
     // Add other command types as needed
 }
 
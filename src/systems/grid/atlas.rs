@@ -0,0 +1,155 @@
+use bevy::app::AppExit;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use crate::resources::GameState;
+use crate::terrain::{TerrainId, TerrainRegistry};
+
+/// Atlas index of every terrain texture variant.
+///
+/// Variants are numbered terrain by terrain in registry order, so the
+/// numbering only depends on the definitions and map generation can hand out
+/// atlas indices before any texture has loaded.
+#[derive(Debug, Clone, Default)]
+pub struct AtlasManifest {
+    /// Index of each terrain's first variant, indexed by [`TerrainId::index`]
+    offsets: Vec<usize>,
+    /// Terrain and variant at each atlas index
+    entries: Vec<(TerrainId, usize)>,
+}
+
+impl AtlasManifest {
+    pub fn new(registry: &TerrainRegistry) -> Self {
+        let mut offsets = Vec::with_capacity(registry.len());
+        let mut entries = Vec::new();
+        for (id, terrain) in registry.iter() {
+            offsets.push(entries.len());
+            entries.extend((0..terrain.textures.len()).map(|variant| (id, variant)));
+        }
+        Self { offsets, entries }
+    }
+
+    /// Atlas index of a terrain's texture variant, if it has that many
+    pub fn index(&self, terrain: TerrainId, variant: usize) -> Option<usize> {
+        let offset = *self.offsets.get(terrain.index())?;
+        let index = offset + variant;
+        (self.entries.get(index)?.0 == terrain).then_some(index)
+    }
+
+    /// Terrain and variant drawn by an atlas index
+    pub fn entry(&self, index: usize) -> Option<(TerrainId, usize)> {
+        self.entries.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Every terrain texture packed into one image at load time, so tiles can
+/// be drawn as batched chunk meshes.
+///
+/// [`Tile::texture_variant`](crate::components::Tile::texture_variant) is an
+/// index into [`TerrainAtlas::region`], numbered as in [`AtlasManifest`].
+#[derive(Resource)]
+pub struct TerrainAtlas {
+    /// For chunk meshes, which carry their own texture coordinates
+    pub material: Handle<ColorMaterial>,
+    /// Where each atlas index sits in the image, in UV space; terrains
+    /// sharing a texture share its region
+    regions: Vec<Rect>,
+}
+
+impl TerrainAtlas {
+    pub fn region(&self, index: usize) -> Option<Rect> {
+        self.regions.get(index).copied()
+    }
+}
+
+/// Terrain textures being loaded, one handle per atlas index
+#[derive(Resource)]
+struct TerrainTextureHandles(Vec<Handle<Image>>);
+
+/// Loads the terrain textures once definitions are in and packs them into
+/// the [`TerrainAtlas`], moving from [`GameState::LoadingTextures`] to
+/// [`GameState::Playing`].
+pub struct TerrainAtlasPlugin;
+
+impl Plugin for TerrainAtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::LoadingTextures), load_terrain_textures)
+            .add_systems(Update, build_terrain_atlas.run_if(in_state(GameState::LoadingTextures)));
+    }
+}
+
+fn load_terrain_textures(mut commands: Commands, asset_server: Res<AssetServer>, registry: Res<TerrainRegistry>) {
+    // The asset server hands out one handle per path, so terrains sharing a
+    // texture, e.g. the kinds of water, load it once
+    let handles = registry.iter()
+        .flat_map(|(_, terrain)| terrain.textures.iter())
+        .map(|path| asset_server.load(path.as_str()))
+        .collect();
+    commands.insert_resource(TerrainTextureHandles(handles));
+}
+
+fn build_terrain_atlas(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    handles: Res<TerrainTextureHandles>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    match asset_server.get_group_load_state(handles.0.iter().map(|handle| handle.id())) {
+        LoadState::Loaded => {}
+        LoadState::Failed => {
+            error!("Some terrain textures failed to load, the map can't be drawn");
+            exit.send(AppExit);
+            return;
+        }
+        _ => return,
+    }
+
+    let mut builder = TextureAtlasBuilder::default();
+    let mut added = HashSet::new();
+    for handle in &handles.0 {
+        if added.insert(handle.id()) {
+            if let Some(image) = images.get(handle) {
+                builder.add_texture(handle.clone(), image);
+            }
+        }
+    }
+    let packed = match builder.finish(&mut images) {
+        Ok(packed) => packed,
+        Err(error) => {
+            error!("Could not pack terrain textures into an atlas: {:?}", error);
+            exit.send(AppExit);
+            return;
+        }
+    };
+
+    // Lay the packed rects out again in manifest order, so an atlas index
+    // picks the same texture as during generation
+    let mut regions = Vec::with_capacity(handles.0.len());
+    for handle in &handles.0 {
+        let rect = packed.get_texture_index(handle)
+            .map(|index| packed.textures[index])
+            .unwrap_or_default();
+        // Keep half a texel inside so filtering never samples the
+        // neighbouring texture
+        regions.push(Rect::from_corners((rect.min + 0.5) / packed.size, (rect.max - 0.5) / packed.size));
+    }
+    info!("Packed {} terrain textures into a {}x{} atlas", regions.len(), packed.size.x, packed.size.y);
+
+    commands.insert_resource(TerrainAtlas {
+        material: materials.add(ColorMaterial::from(packed.texture)),
+        regions,
+    });
+    commands.remove_resource::<TerrainTextureHandles>();
+    next_state.set(GameState::Playing);
+}
@@ -0,0 +1,341 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::primitives::Aabb;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::utils::{HashMap, HashSet};
+use super::atlas::TerrainAtlas;
+use crate::components::{BaseTint, HexChunk, Tile, TileTint};
+use crate::constants::{CHUNK_SIZE, TERRAIN_BLEND_SAMPLE, TERRAIN_BLEND_WIDTH, TERRAIN_Z};
+use crate::hex_map::HexMap;
+use crate::terrain::TerrainRegistry;
+use crate::utils::{hexagon_mesh, Hex, HexDirection, HexEdge, HexLayout};
+
+/// Chunk a hex is drawn in. Chunks are [`CHUNK_SIZE`] by [`CHUNK_SIZE`]
+/// parallelograms in axial space.
+pub fn chunk_of(hex: Hex) -> IVec2 {
+    IVec2::new(hex.q.div_euclid(CHUNK_SIZE), hex.r.div_euclid(CHUNK_SIZE))
+}
+
+/// Hexes a chunk covers, whether or not they are part of the map
+pub fn chunk_hexes(chunk: IVec2) -> impl Iterator<Item = Hex> {
+    let origin = chunk * CHUNK_SIZE;
+    (0..CHUNK_SIZE).flat_map(move |r| (0..CHUNK_SIZE).map(move |q| Hex::new(origin.x + q, origin.y + r)))
+}
+
+/// Chunk mesh entities and the chunks waiting to be rebuilt.
+///
+/// Tile entities only carry game data; what is on screen is one mesh per
+/// chunk, rebuilt whenever a tile in it is added, changed, retinted or
+/// despawned. Edges blend into neighbouring terrain, so a tile changing
+/// terrain also rebuilds the chunks of its neighbours.
+#[derive(Resource, Debug, Default)]
+pub struct TileChunks {
+    chunks: HashMap<IVec2, Entity>,
+    dirty: HashSet<IVec2>,
+    /// Hex each tile entity was last drawn at, so a despawned or moved tile
+    /// still finds its old chunk and neighbours
+    tile_hexes: HashMap<Entity, Hex>,
+}
+
+impl TileChunks {
+    /// Queue the chunk drawing `hex` for a rebuild
+    pub fn mark_dirty(&mut self, hex: Hex) {
+        self.dirty.insert(chunk_of(hex));
+    }
+
+    /// Queue the chunks drawing `hex` and its neighbours, whose edges blend
+    /// into it
+    fn mark_dirty_with_neighbors(&mut self, hex: Hex) {
+        self.mark_dirty(hex);
+        for neighbor in hex.neighbors() {
+            self.mark_dirty(neighbor);
+        }
+    }
+
+    /// Mesh entity of a chunk, if it has been built
+    pub fn chunk_entity(&self, chunk: IVec2) -> Option<Entity> {
+        self.chunks.get(&chunk).copied()
+    }
+}
+
+/// Queue the chunks of tiles that were added, changed, retinted or despawned
+#[allow(clippy::type_complexity)]
+pub fn mark_dirty_chunks_system(
+    mut chunks: ResMut<TileChunks>,
+    tiles: Query<(Entity, Ref<Tile>), Or<(Changed<Tile>, Changed<TileTint>)>>,
+    mut removed_tiles: RemovedComponents<Tile>,
+) {
+    for (entity, tile) in tiles.iter() {
+        let previous = chunks.tile_hexes.insert(entity, tile.hex);
+        if !tile.is_changed() {
+            // Only the tint changed, which neighbours don't blend with
+            chunks.mark_dirty(tile.hex);
+            continue;
+        }
+        if let Some(previous) = previous {
+            chunks.mark_dirty_with_neighbors(previous);
+        }
+        chunks.mark_dirty_with_neighbors(tile.hex);
+    }
+
+    for entity in removed_tiles.iter() {
+        if let Some(hex) = chunks.tile_hexes.remove(&entity) {
+            chunks.mark_dirty_with_neighbors(hex);
+        }
+    }
+}
+
+/// Rebuild the mesh of every dirty chunk once the [`TerrainAtlas`] is ready,
+/// spawning and despawning chunk entities as tiles come and go
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_tile_chunks_system(
+    mut commands: Commands,
+    mut chunks: ResMut<TileChunks>,
+    atlas: Option<Res<TerrainAtlas>>,
+    registry: Option<Res<TerrainRegistry>>,
+    hex_map: Res<HexMap>,
+    layout: Res<HexLayout>,
+    tiles: Query<(&Tile, &TileTint, &BaseTint)>,
+    chunk_meshes: Query<&Mesh2dHandle, With<HexChunk>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let (Some(atlas), Some(registry)) = (atlas, registry) else {
+        return;
+    };
+    if chunks.dirty.is_empty() {
+        return;
+    }
+
+    let hexagon = HexagonTemplate::new(&layout);
+    let dirty: Vec<IVec2> = chunks.dirty.drain().collect();
+    for chunk in dirty {
+        let tile_at = |hex: Hex| hex_map.get(hex).and_then(|cell| tiles.get(cell.entity).ok());
+        let tiles_in_chunk: Vec<TileView> = chunk_hexes(chunk).filter_map(tile_at).collect();
+        let mesh = hexagon.chunk_mesh(&tiles_in_chunk, tile_at, &layout, &atlas, &registry);
+        let existing = chunks.chunks.get(&chunk).copied();
+
+        match (mesh, existing) {
+            (Some(mesh), Some(entity)) => {
+                if let Some(existing) = chunk_meshes.get(entity).ok().and_then(|handle| meshes.get_mut(&handle.0)) {
+                    *existing = mesh;
+                }
+                // Bounds are only computed for entities without them
+                commands.entity(entity).remove::<Aabb>();
+            }
+            (Some(mesh), None) => {
+                let entity = commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: meshes.add(mesh).into(),
+                        material: atlas.material.clone(),
+                        transform: Transform::from_xyz(0.0, 0.0, TERRAIN_Z),
+                        ..default()
+                    },
+                    HexChunk { coord: chunk },
+                )).id();
+                chunks.chunks.insert(chunk, entity);
+            }
+            (None, Some(entity)) => {
+                commands.entity(entity).despawn();
+                chunks.chunks.remove(&chunk);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// What chunk meshes need of a tile entity
+type TileView<'a> = (&'a Tile, &'a TileTint, &'a BaseTint);
+
+/// Corners and triangles of one hex from [`hexagon_mesh`], with the texture
+/// coordinates that lay tile art over it the same way a tile sprite would
+struct HexagonTemplate {
+    offsets: Vec<Vec2>,
+    /// Texture coordinates of each offset within a single tile texture
+    uvs: Vec<Vec2>,
+    indices: Vec<u32>,
+    /// Undoes the rotation tile art is drawn with
+    unrotate: Vec2,
+    /// Size tile art is drawn at
+    size: Vec2,
+}
+
+impl HexagonTemplate {
+    fn new(layout: &HexLayout) -> Self {
+        let mesh = hexagon_mesh(layout);
+        let offsets: Vec<Vec2> = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => {
+                positions.iter().map(|&[x, y, _]| Vec2::new(x, y)).collect()
+            }
+            _ => Vec::new(),
+        };
+        let indices = match mesh.indices() {
+            Some(Indices::U32(indices)) => indices.clone(),
+            Some(Indices::U16(indices)) => indices.iter().map(|&index| index as u32).collect(),
+            None => Vec::new(),
+        };
+
+        // Tile art is pointy-top and drawn rotated and mirrored in y, see
+        // `HexLayout::tile_sprite_size`; undo both to find where each corner
+        // falls in the texture
+        let mut template = Self {
+            offsets,
+            uvs: Vec::new(),
+            indices,
+            unrotate: Vec2::from_angle(-layout.tile_sprite_rotation()),
+            size: layout.tile_sprite_size(),
+        };
+        template.uvs = template.offsets.iter().map(|&offset| template.uv(offset)).collect();
+        template
+    }
+
+    /// Texture coordinates of a point `offset` from a hex centre
+    fn uv(&self, offset: Vec2) -> Vec2 {
+        let local = self.unrotate.rotate(offset);
+        Vec2::new(local.x / self.size.x + 0.5, local.y / self.size.y + 0.5)
+    }
+
+    /// Mesh of every tile in a chunk, or `None` if it has none.
+    ///
+    /// Where a neighbour's terrain [blends over](TerrainRegistry::blends_over)
+    /// a tile's, a strip along their shared edge is drawn with the
+    /// neighbour's texture, fading out towards the tile's centre. Strips come
+    /// after every hex in the mesh so they draw on top.
+    fn chunk_mesh<'a>(
+        &self,
+        tiles: &[TileView<'a>],
+        tile_at: impl Fn(Hex) -> Option<TileView<'a>>,
+        layout: &HexLayout,
+        atlas: &TerrainAtlas,
+        registry: &TerrainRegistry,
+    ) -> Option<Mesh> {
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+
+        for &(tile, tint, _) in tiles {
+            let Some(region) = atlas.region(tile.texture_variant) else {
+                continue;
+            };
+
+            let first = positions.len() as u32;
+            let center = layout.hex_to_world(tile.hex).truncate();
+            let color = tint.0.as_linear_rgba_f32();
+            for (offset, uv) in self.offsets.iter().zip(&self.uvs) {
+                let position = center + *offset;
+                let uv = region.min + *uv * region.size();
+                positions.push([position.x, position.y, 0.0]);
+                uvs.push([uv.x, uv.y]);
+                colors.push(color);
+            }
+            indices.extend(self.indices.iter().map(|index| first + index));
+        }
+
+        for &(tile, tint, base) in tiles {
+            let center = layout.hex_to_world(tile.hex).truncate();
+            for direction in HexDirection::ALL {
+                let Some((neighbor, _, neighbor_base)) = tile_at(tile.hex.neighbor(direction)) else {
+                    continue;
+                };
+                if !registry.blends_over(neighbor.terrain, tile.terrain) {
+                    continue;
+                }
+                let Some(region) = atlas.region(neighbor.texture_variant) else {
+                    continue;
+                };
+
+                let (start, end) = layout.edge_to_world(HexEdge::between(tile.hex, direction));
+                let (mut start, mut end) = (start - center, end - center);
+                if start.perp_dot(end) < 0.0 {
+                    // Same winding as the hex's own triangles
+                    std::mem::swap(&mut start, &mut end);
+                }
+                let inset = 1.0 - TERRAIN_BLEND_WIDTH;
+                let edge_color = blend_color(neighbor_base.0, tint.0, base.0).as_linear_rgba_f32();
+                let inner_color = [edge_color[0], edge_color[1], edge_color[2], 0.0];
+
+                let first = positions.len() as u32;
+                let corners = [
+                    (start, edge_color),
+                    (end, edge_color),
+                    (end * inset, inner_color),
+                    (start * inset, inner_color),
+                ];
+                for (offset, color) in corners {
+                    let position = center + offset;
+                    // Sample the middle of the neighbour's art rather than
+                    // the outline around its edges
+                    let uv = Vec2::splat(0.5) + (self.uv(offset) - 0.5) * TERRAIN_BLEND_SAMPLE;
+                    let uv = region.min + uv * region.size();
+                    positions.push([position.x, position.y, 0.0]);
+                    uvs.push([uv.x, uv.y]);
+                    colors.push(color);
+                }
+                indices.extend([0, 1, 2, 0, 2, 3].map(|index| first + index));
+            }
+        }
+
+        if positions.is_empty() {
+            return None;
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; positions.len()]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        Some(mesh)
+    }
+}
+
+/// Colour of a neighbour's terrain blending over a tile: the neighbour's
+/// own tint, carrying over whatever highlight the tile's tint adds to its
+/// base tint
+fn blend_color(neighbor_base: Color, tint: Color, base: Color) -> Color {
+    let highlight = |tint: f32, base: f32| if base > 0.0 { tint / base } else { 1.0 };
+    Color::rgba(
+        neighbor_base.r() * highlight(tint.r(), base.r()),
+        neighbor_base.g() * highlight(tint.g(), base.g()),
+        neighbor_base.b() * highlight(tint.b(), base.b()),
+        tint.a(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Corners of the pointy-top hexagon tile art is painted in, in UV space
+    const ART_CORNERS: [Vec2; 6] = [
+        Vec2::new(0.5, 0.0),
+        Vec2::new(1.0, 0.25),
+        Vec2::new(1.0, 0.75),
+        Vec2::new(0.5, 1.0),
+        Vec2::new(0.0, 0.75),
+        Vec2::new(0.0, 0.25),
+    ];
+
+    #[test]
+    fn hex_corners_land_on_tile_art_corners_in_any_layout() {
+        let squashed = |mut layout: HexLayout| {
+            layout.size.y *= 0.6;
+            layout
+        };
+        let layouts = [
+            HexLayout::pointy(30.0),
+            HexLayout::flat(30.0),
+            squashed(HexLayout::pointy(30.0)),
+            squashed(HexLayout::flat(30.0)),
+        ];
+        for layout in layouts {
+            let template = HexagonTemplate::new(&layout);
+            // The first vertex is the centre
+            assert!(template.uvs[0].abs_diff_eq(Vec2::splat(0.5), 1e-5));
+            for corner in ART_CORNERS {
+                let hits = template.uvs.iter().filter(|uv| uv.abs_diff_eq(corner, 1e-5)).count();
+                assert_eq!(hits, 1, "art corner {} in {:?}", corner, layout);
+            }
+        }
+    }
+}
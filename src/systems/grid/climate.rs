@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use bevy::utils::HashMap;
+use noise::NoiseFn;
+use crate::utils::{Hex, HexDirection};
+use super::MapGenConfig;
+
+/// Settings for the temperature and moisture model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimateSettings {
+    /// Temperature on the equator at sea level, before noise
+    pub base_temperature: f64,
+    /// How much colder the poles (top and bottom rows) are than the equator
+    pub latitude_cooling: f64,
+    /// Cooling per unit of height above sea level
+    pub lapse_rate: f64,
+    /// Distance to water, in hexes, at which moisture has halved
+    pub moisture_falloff: f64,
+    /// Direction the prevailing wind blows towards
+    pub wind: HexDirection,
+    /// How many hexes upwind water still brings rain
+    pub wind_reach: i32,
+    /// Share of moisture that comes from the wind rather than distance
+    pub wind_weight: f64,
+    /// Strength of the noise added to both values
+    pub noise_amount: f64,
+}
+
+/// Temperature and moisture of a hex, both from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Climate {
+    pub temperature: f32,
+    pub moisture: f32,
+}
+
+/// Temperature and moisture for every land hex.
+///
+/// Temperature falls off towards the top and bottom of the map and with
+/// height. Moisture is highest next to water and where the prevailing wind
+/// has recently crossed water; mountains upwind cast a rain shadow.
+pub fn compute_climate(
+    hexes: &[Hex],
+    heights: &HashMap<Hex, f64>,
+    config: &MapGenConfig,
+    noise: &impl NoiseFn<f64, 2>,
+    noise_coords: impl Fn(Hex) -> [f64; 2],
+) -> HashMap<Hex, Climate> {
+    let settings = &config.climate;
+    let is_water = |hex: &Hex| heights.get(hex).is_some_and(|&height| height < config.sea_level);
+    let is_mountain = |hex: &Hex| heights.get(hex).is_some_and(|&height| height > config.feature_thresholds.mountain);
+
+    // Latitude runs from 0 on the middle row to 1 on the top and bottom rows
+    let (min_r, max_r) = hexes.iter()
+        .fold((i32::MAX, i32::MIN), |(min, max), hex| (min.min(hex.r), max.max(hex.r)));
+    let equator = (min_r + max_r) as f64 / 2.0;
+    let half_height = ((max_r - min_r) as f64 / 2.0).max(1.0);
+
+    let water_distance = distance_to_water(hexes, heights, config.sea_level);
+    let upwind = settings.wind.opposite();
+
+    let mut climate = HashMap::new();
+    for &hex in hexes {
+        if is_water(&hex) {
+            continue;
+        }
+
+        let [x, z] = noise_coords(hex);
+        let latitude = (hex.r as f64 - equator).abs() / half_height;
+        let height = heights[&hex] - config.sea_level;
+        let temperature = settings.base_temperature
+            - settings.latitude_cooling * latitude
+            - settings.lapse_rate * height
+            + settings.noise_amount * noise.get([x, z]);
+
+        // Walk upwind until the air last picked up water or hit a mountain
+        let mut wind_moisture = 0.0;
+        let mut upwind_hex = hex;
+        for step in 1..=settings.wind_reach {
+            upwind_hex = upwind_hex.neighbor(upwind);
+            if is_water(&upwind_hex) {
+                wind_moisture = 1.0 - (step - 1) as f64 / settings.wind_reach as f64;
+                break;
+            }
+            if is_mountain(&upwind_hex) || !heights.contains_key(&upwind_hex) {
+                break;
+            }
+        }
+
+        let distance = water_distance.get(&hex).copied().unwrap_or(u32::MAX) as f64;
+        let near_water = settings.moisture_falloff / (settings.moisture_falloff + distance);
+        let moisture = near_water * (1.0 - settings.wind_weight)
+            + wind_moisture * settings.wind_weight
+            // Sample away from the temperature noise so the two don't line up
+            + settings.noise_amount * noise.get([x + 517.0, z - 311.0]);
+
+        climate.insert(hex, Climate {
+            temperature: temperature.clamp(0.0, 1.0) as f32,
+            moisture: moisture.clamp(0.0, 1.0) as f32,
+        });
+    }
+
+    climate
+}
+
+/// Steps from each land hex to the nearest water hex; hexes with no water
+/// on the map are left out
+fn distance_to_water(hexes: &[Hex], heights: &HashMap<Hex, f64>, sea_level: f64) -> HashMap<Hex, u32> {
+    let mut distances = HashMap::new();
+    let mut frontier = VecDeque::new();
+    for &hex in hexes {
+        if heights[&hex] < sea_level {
+            distances.insert(hex, 0);
+            frontier.push_back(hex);
+        }
+    }
+
+    while let Some(hex) = frontier.pop_front() {
+        let distance = distances[&hex];
+        for neighbor in hex.neighbors() {
+            if heights.contains_key(&neighbor) && !distances.contains_key(&neighbor) {
+                distances.insert(neighbor, distance + 1);
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
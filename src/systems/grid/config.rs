@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use crate::utils::MapShape;
+use crate::utils::HexDirection;
+use crate::constants::STARTING_UNITS;
+use super::{ClimateSettings, MapGenerator, PassPipeline, RiverSettings, SmoothingSettings, StartSettings, WfcGenerator, WfcSettings};
+
+/// Settings for a fractal Brownian motion noise layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FbmSettings {
+    pub frequency: f64,
+    pub octaves: usize,
+    pub persistence: f64,
+}
+
+/// Settings for the turbulence layer that places terrain features
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurbulenceSettings {
+    pub frequency: f64,
+    pub power: f64,
+}
+
+/// Noise values above which a terrain feature replaces the base biome.
+/// `mountain` is compared against the heightmap, the rest against feature
+/// noise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureThresholds {
+    pub mountain: f64,
+    pub cactus: f64,
+    pub rocks: f64,
+    pub forest: f64,
+}
+
+/// Built-in map types. Each one tweaks the default [`MapGenConfig`] and
+/// picks the passes its generator runs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MapPreset {
+    /// Large landmasses split by oceans
+    #[default]
+    Continents,
+    /// Scattered islands in a wide sea
+    Archipelago,
+    /// Hot, dry and almost without water
+    DesertWorld,
+    /// Cold, strange and dry, without rivers
+    AlienPlanet,
+    /// Built by wave function collapse from the hand-authored adjacency
+    /// rules in the terrain definitions
+    Handcrafted,
+}
+
+impl MapPreset {
+    pub const ALL: [MapPreset; 5] = [
+        MapPreset::Continents,
+        MapPreset::Archipelago,
+        MapPreset::DesertWorld,
+        MapPreset::AlienPlanet,
+        MapPreset::Handcrafted,
+    ];
+
+    /// Display name, e.g. "Desert World"
+    pub fn name(self) -> &'static str {
+        match self {
+            MapPreset::Continents => "Continents",
+            MapPreset::Archipelago => "Archipelago",
+            MapPreset::DesertWorld => "Desert World",
+            MapPreset::AlienPlanet => "Alien Planet",
+            MapPreset::Handcrafted => "Handcrafted",
+        }
+    }
+
+    /// Look a preset up by name, ignoring case, spaces, dashes and
+    /// underscores, so "desert-world" finds [`MapPreset::DesertWorld`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalize = |name: &str| -> String {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .flat_map(char::to_lowercase)
+                .collect()
+        };
+        let name = normalize(name);
+        Self::ALL.into_iter().find(|preset| normalize(preset.name()) == name)
+    }
+
+    /// Generator that builds maps of this type
+    pub fn generator(self) -> Box<dyn MapGenerator> {
+        match self {
+            MapPreset::Continents | MapPreset::Archipelago | MapPreset::DesertWorld | MapPreset::AlienPlanet => {
+                Box::new(PassPipeline::standard())
+            }
+            MapPreset::Handcrafted => Box::new(WfcGenerator),
+        }
+    }
+}
+
+/// Everything that drives map generation.
+///
+/// Generation is a pure function of this config and the terrain definitions:
+/// the same seed, settings and definitions always produce the same tile list.
+/// The climate-to-biome table lives with the terrains themselves, see
+/// [`crate::terrain::ClimateTable`].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct MapGenConfig {
+    pub seed: u64,
+    /// Map type this config was made for; also picks the generator
+    pub preset: MapPreset,
+    pub shape: MapShape,
+    /// Noise that breaks up temperature and moisture bands
+    pub climate_noise: FbmSettings,
+    /// Heightmap; also decides where mountains rise
+    pub elevation_noise: FbmSettings,
+    pub feature_noise: TurbulenceSettings,
+    /// Heightmap value below which hexes become water
+    pub sea_level: f64,
+    /// Enclosed water bodies up to this many hexes become lakes; larger
+    /// ones count as ocean
+    pub max_lake_size: usize,
+    pub rivers: RiverSettings,
+    pub climate: ClimateSettings,
+    pub feature_thresholds: FeatureThresholds,
+    /// Cleanup after features are placed; per-terrain rules live with the
+    /// terrain definitions, see [`crate::terrain::SmoothingRules`]
+    pub smoothing: SmoothingSettings,
+    pub wfc: WfcSettings,
+    pub start: StartSettings,
+}
+
+impl MapGenConfig {
+    /// Default settings with a fixed seed, e.g. one copied from a bug report
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            preset: MapPreset::Continents,
+            shape: MapShape::default(),
+            climate_noise: FbmSettings {
+                frequency: 0.02,
+                octaves: 6,
+                persistence: 0.6,
+            },
+            elevation_noise: FbmSettings {
+                frequency: 0.025,
+                octaves: 4,
+                persistence: 0.5,
+            },
+            feature_noise: TurbulenceSettings {
+                frequency: 0.03,
+                power: 0.7,
+            },
+            sea_level: -0.55,     // ~15% water
+            max_lake_size: 40,
+            rivers: RiverSettings {
+                count: 40,
+                min_source_height: 0.8,
+                min_length: 4,
+            },
+            climate: ClimateSettings {
+                base_temperature: 1.0,
+                latitude_cooling: 0.8,
+                lapse_rate: 0.25,
+                moisture_falloff: 6.0,
+                wind: HexDirection::East,
+                wind_reach: 12,
+                wind_weight: 0.4,
+                noise_amount: 0.25,
+            },
+            feature_thresholds: FeatureThresholds {
+                mountain: 0.87,
+                cactus: 0.85,
+                rocks: 0.3,
+                forest: -0.1,
+            },
+            smoothing: SmoothingSettings {
+                iterations: 3,
+                isolation_threshold: 5,
+            },
+            wfc: WfcSettings {
+                max_backtracks: 1000,
+                attempts: 3,
+            },
+            start: StartSettings {
+                players: 2,
+                units_per_player: STARTING_UNITS.len(),
+                area_radius: 2,
+                attempts: 16,
+                fairness: 0.5,
+            },
+        }
+    }
+}
+
+impl MapGenConfig {
+    /// Settings for a built-in map type
+    pub fn for_preset(preset: MapPreset, seed: u64) -> Self {
+        let mut config = Self::with_seed(seed);
+        config.preset = preset;
+        match preset {
+            MapPreset::Continents => {}
+            MapPreset::Archipelago => {
+                // Smaller, more frequent bumps with most of them under water
+                config.elevation_noise.frequency = 0.05;
+                config.sea_level = 0.1;
+                config.max_lake_size = 10;
+                config.rivers.count = 15;
+                config.rivers.min_source_height = 0.4;
+                config.rivers.min_length = 3;
+            }
+            MapPreset::DesertWorld => {
+                config.sea_level = -1.0;
+                config.climate.base_temperature = 1.3;
+                config.climate.latitude_cooling = 0.3;
+                config.climate.moisture_falloff = 2.0;
+                config.climate.wind_weight = 0.2;
+                config.rivers.count = 8;
+            }
+            MapPreset::AlienPlanet => {
+                config.sea_level = -0.8;
+                config.climate.base_temperature = 0.35;
+                config.climate.latitude_cooling = 0.3;
+                config.rivers.count = 0;
+            }
+            MapPreset::Handcrafted => {}
+        }
+        config
+    }
+}
+
+impl Default for MapGenConfig {
+    /// Default settings with a fresh random seed
+    fn default() -> Self {
+        Self::with_seed(rand::random())
+    }
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::{prelude::*, rngs::StdRng};
+use crate::components::DepositIcon;
+use crate::constants::{DEPOSIT_ICON_SCALE, DEPOSIT_Z};
+use crate::hex_map::HexMap;
+use crate::terrain::{DepositId, TerrainId, TerrainRegistry};
+use crate::utils::{Hex, HexLayout};
+
+/// Resource deposits for the finished terrain.
+///
+/// Hexes are visited in order. Each deposit allowed on a hex's terrain is
+/// rolled against its `chance`, in file order, and the first that succeeds
+/// and has no deposit of the same kind within `min_distance` is placed, so
+/// a hex holds at most one deposit.
+pub fn place_deposits(
+    hexes: &[Hex],
+    terrain: &HashMap<Hex, TerrainId>,
+    registry: &TerrainRegistry,
+    rng: &mut StdRng,
+) -> HashMap<Hex, DepositId> {
+    let mut deposits = HashMap::new();
+    let mut placed: Vec<Vec<Hex>> = vec![Vec::new(); registry.deposits().count()];
+
+    for &hex in hexes {
+        let Some(&terrain) = terrain.get(&hex) else {
+            continue;
+        };
+        for &deposit in registry.deposits_on(terrain) {
+            let definition = registry.deposit(deposit);
+            if !rng.gen_bool(definition.chance as f64) {
+                continue;
+            }
+            let same_kind = &mut placed[deposit.index()];
+            if same_kind.iter().any(|other| other.distance(hex) < definition.min_distance) {
+                continue;
+            }
+            same_kind.push(hex);
+            deposits.insert(hex, deposit);
+            break;
+        }
+    }
+
+    deposits
+}
+
+/// Spawn an icon over every tile in the map holding a deposit
+pub(crate) fn spawn_deposit_icons(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    layout: &HexLayout,
+    registry: &TerrainRegistry,
+    hex_map: &HexMap,
+) {
+    let icons: Vec<Handle<Image>> = registry.deposits()
+        .map(|(_, deposit)| asset_server.load(deposit.icon.as_str()))
+        .collect();
+    let size = layout.tile_sprite_size() * DEPOSIT_ICON_SCALE;
+
+    for (hex, deposit) in hex_map.deposits() {
+        commands.spawn((
+            SpriteBundle {
+                texture: icons[deposit.index()].clone(),
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    ..default()
+                },
+                // Flipped like the tiles so the art is upright under the
+                // mirrored camera
+                transform: Transform::from_translation(layout.hex_to_world(hex).truncate().extend(DEPOSIT_Z))
+                    .with_scale(Vec3::new(1.0, -1.0, 1.0)),
+                ..default()
+            },
+            DepositIcon { hex, deposit },
+        ));
+    }
+}
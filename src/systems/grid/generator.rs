@@ -0,0 +1,259 @@
+use bevy::log::debug;
+use bevy::utils::{HashMap, HashSet};
+use noise::{MultiFractal, NoiseFn, Fbm, Turbulence, Perlin};
+use rand::{prelude::*, rngs::StdRng};
+use crate::components::Tile;
+use crate::constants::RIVER_FOOD_BONUS;
+use crate::terrain::{DepositId, TerrainId, TerrainRegistry, Yields};
+use crate::utils::{Hex, HexLayout};
+use super::{AtlasManifest, Climate, GeneratedMap, MapGenConfig, River};
+use super::passes::*;
+
+/// Noise generators shared by every pass, seeded from the map seed
+pub struct MapNoise {
+    offset_x: f64,
+    offset_z: f64,
+    pub climate: Fbm<Perlin>,
+    pub elevation: Fbm<Perlin>,
+    pub feature: Turbulence<Perlin, Perlin>,
+}
+
+impl MapNoise {
+    fn new(config: &MapGenConfig, rng: &mut StdRng) -> Self {
+        // Random offsets to avoid sampling near origin
+        let offset_x = rng.gen_range(-1000.0..1000.0);
+        let offset_z = rng.gen_range(-1000.0..1000.0);
+
+        // Create multi-octave noise generators
+        let climate = Fbm::<Perlin>::new(rng.gen())
+            .set_octaves(config.climate_noise.octaves)
+            .set_frequency(config.climate_noise.frequency)
+            .set_persistence(config.climate_noise.persistence);
+
+        let elevation = Fbm::<Perlin>::new(rng.gen())
+            .set_octaves(config.elevation_noise.octaves)
+            .set_frequency(config.elevation_noise.frequency)
+            .set_persistence(config.elevation_noise.persistence);
+
+        let feature = Turbulence::<Perlin, Perlin>::new(Perlin::new(rng.gen()))
+            .set_frequency(config.feature_noise.frequency)
+            .set_power(config.feature_noise.power);
+
+        Self { offset_x, offset_z, climate, elevation, feature }
+    }
+
+    /// Position of a hex in noise space
+    pub fn coords(&self, hex: Hex) -> [f64; 2] {
+        Self::noise_coords(hex.q, hex.r, self.offset_x, self.offset_z)
+    }
+
+    /// Position used to pick texture variants, away from the feature samples
+    pub fn variant_coords(&self, hex: Hex) -> [f64; 2] {
+        Self::noise_coords(hex.q * 2, hex.r * 2, self.offset_x * 2.0, self.offset_z * 2.0)
+    }
+
+    fn noise_coords(q: i32, r: i32, offset_x: f64, offset_z: f64) -> [f64; 2] {
+        // Increased the scaling factors slightly for more variation
+        let x = (q as f64) * 1.0 - (r as f64) * 0.6 + offset_x;
+        let z = (r as f64) * 0.9 + offset_z;
+        [x, z]
+    }
+}
+
+/// Everything a map generation pass reads and writes
+pub struct MapGenContext<'a> {
+    pub config: &'a MapGenConfig,
+    pub registry: &'a TerrainRegistry,
+    /// Every random value comes from this seeded generator, in pass order,
+    /// so a seed always reproduces the same map
+    pub rng: StdRng,
+    pub noise: MapNoise,
+    /// Hexes in the map, in a stable order
+    pub hexes: Vec<Hex>,
+    /// Heightmap value per hex; below `config.sea_level` is water
+    pub heights: HashMap<Hex, f64>,
+    /// Climate of each land hex
+    pub climate: HashMap<Hex, Climate>,
+    /// Terrain chosen so far; hexes without one are left out of the map
+    pub terrain: HashMap<Hex, TerrainId>,
+    pub rivers: Vec<River>,
+    /// Resource deposit on each hex that has one
+    pub deposits: HashMap<Hex, DepositId>,
+    /// Start hex of each player
+    pub start_positions: Vec<Hex>,
+}
+
+impl<'a> MapGenContext<'a> {
+    pub fn new(config: &'a MapGenConfig, registry: &'a TerrainRegistry) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let noise = MapNoise::new(config, &mut rng);
+        Self {
+            config,
+            registry,
+            rng,
+            noise,
+            hexes: Vec::new(),
+            heights: HashMap::new(),
+            climate: HashMap::new(),
+            terrain: HashMap::new(),
+            rivers: Vec::new(),
+            deposits: HashMap::new(),
+            start_positions: Vec::new(),
+        }
+    }
+
+    /// Whether `hex` lies below sea level
+    pub fn is_water(&self, hex: Hex) -> bool {
+        self.heights.get(&hex).is_some_and(|&height| height < self.config.sea_level)
+    }
+
+    /// Hexes with a river along at least one of their edges
+    pub fn river_banks(&self) -> HashSet<Hex> {
+        self.rivers.iter()
+            .flat_map(|river| &river.edges)
+            .flat_map(|edge| {
+                let (a, b) = edge.hexes();
+                [a, b]
+            })
+            .collect()
+    }
+
+    /// What `hex` produces with the terrain, deposits and rivers placed so
+    /// far; `river_banks` comes from [`MapGenContext::river_banks`]
+    pub fn yields(&self, hex: Hex, river_banks: &HashSet<Hex>) -> Yields {
+        let Some(&terrain) = self.terrain.get(&hex) else {
+            return Yields::default();
+        };
+        let mut yields = self.registry.yields(terrain, self.deposits.get(&hex).copied());
+        if river_banks.contains(&hex) && !self.registry.is_water(terrain) {
+            yields += Yields { food: RIVER_FOOD_BONUS, ..Yields::default() };
+        }
+        yields
+    }
+
+    /// Tiles for every hex with terrain, in [`MapGenContext::hexes`] order
+    pub fn finish(self, layout: &HexLayout) -> GeneratedMap {
+        let river_banks = self.river_banks();
+        let manifest = AtlasManifest::new(self.registry);
+
+        let mut tiles = Vec::new();
+        for &hex in &self.hexes {
+            let Some(&terrain) = self.terrain.get(&hex) else {
+                continue;
+            };
+
+            // Use different noise coordinates for variant selection
+            let variant_count = self.registry.get(terrain).textures.len();
+            let variant_value = self.noise.feature.get(self.noise.variant_coords(hex));
+            let variant = (variant_value.abs() * variant_count as f64) as usize % variant_count;
+            let texture_variant = manifest.index(terrain, variant).unwrap_or_default();
+
+            tiles.push(Tile {
+                id: tiles.len() as i32,
+                hex,
+                position: layout.hex_to_world(hex),
+                terrain,
+                texture_variant,
+                elevation: self.heights.get(&hex).map_or(0.0, |height| (height - self.config.sea_level) as f32),
+                deposit: self.deposits.get(&hex).copied(),
+                yields: self.yields(hex, &river_banks),
+            });
+        }
+
+        GeneratedMap {
+            tiles,
+            rivers: self.rivers,
+            start_positions: self.start_positions,
+        }
+    }
+}
+
+/// One step of map generation, e.g. sampling the heightmap or placing water
+pub trait MapPass: Send + Sync {
+    /// Name shown in generation logs
+    fn name(&self) -> &'static str;
+
+    fn run(&self, context: &mut MapGenContext);
+}
+
+/// Anything that can produce a map from a config and the terrain registry
+pub trait MapGenerator: Send + Sync {
+    fn generate(&self, config: &MapGenConfig, registry: &TerrainRegistry, layout: &HexLayout) -> GeneratedMap;
+}
+
+/// A generator that runs a list of passes in order over one
+/// [`MapGenContext`]
+#[derive(Default)]
+pub struct PassPipeline {
+    passes: Vec<Box<dyn MapPass>>,
+}
+
+impl PassPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a pass to run after the ones already added
+    pub fn with_pass(mut self, pass: impl MapPass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Shape, heightmap, climate, biomes, features, smoothing, water,
+    /// rivers, deposits and start positions: the passes every built-in
+    /// preset starts from
+    pub fn standard() -> Self {
+        Self::new()
+            .with_pass(ShapePass)
+            .with_pass(ElevationPass)
+            .with_pass(ClimatePass)
+            .with_pass(BiomePass)
+            .with_pass(FeaturePass)
+            .with_pass(SmoothingPass)
+            .with_pass(WaterPass)
+            .with_pass(RiverPass)
+            .with_pass(DepositPass)
+            .with_pass(StartPass)
+    }
+}
+
+impl MapGenerator for PassPipeline {
+    fn generate(&self, config: &MapGenConfig, registry: &TerrainRegistry, layout: &HexLayout) -> GeneratedMap {
+        let mut context = MapGenContext::new(config, registry);
+        for pass in &self.passes {
+            debug!("Running map pass: {}", pass.name());
+            pass.run(&mut context);
+        }
+        context.finish(layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::WfcGenerator;
+    use crate::terrain::SHIPPED_DEFINITIONS;
+
+    fn generate_twice(generator: &dyn MapGenerator, seed: u64) -> (Vec<Tile>, Vec<Tile>) {
+        let registry = TerrainRegistry::from_ron(SHIPPED_DEFINITIONS).unwrap();
+        let layout = HexLayout::default();
+        let config = MapGenConfig::with_seed(seed);
+        let first = generator.generate(&config, &registry, &layout);
+        let second = generator.generate(&config, &registry, &layout);
+        (first.tiles, second.tiles)
+    }
+
+    #[test]
+    fn standard_pipeline_is_deterministic() {
+        let (first, second) = generate_twice(&PassPipeline::standard(), 7);
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn wfc_generator_is_deterministic() {
+        let (first, second) = generate_twice(&WfcGenerator, 7);
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+}
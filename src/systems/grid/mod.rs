@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy::ui::Style;
+use crate::components::*;
+use crate::hex_map::{HexCell, HexMap};
+use crate::resources::StartPositions;
+use crate::constants::ELEVATION_SHADE_RANGE;
+use crate::terrain::TerrainRegistry;
+use crate::utils::*;
+
+// Map generation settings
+pub mod config;
+// Hydrology pass and river overlays
+pub mod rivers;
+// Temperature and moisture model for biome selection
+pub mod climate;
+// MapGenerator trait and the pass pipeline
+pub mod generator;
+// Built-in generation passes
+pub mod passes;
+// Cellular-automaton cleanup of land terrain
+pub mod smoothing;
+// Wave function collapse over hand-authored adjacency rules
+pub mod wfc;
+// Resource deposit placement and icons
+pub mod deposits;
+// Balanced player start positions
+pub mod start;
+// Terrain textures packed into one atlas at load time
+pub mod atlas;
+// One mesh per chunk of tiles instead of a sprite per tile
+pub mod chunks;
+// Grid lines and coordinate labels for debugging
+pub mod overlay;
+
+pub use self::config::*;
+pub use self::rivers::*;
+pub use self::climate::*;
+pub use self::generator::*;
+pub use self::smoothing::*;
+pub use self::wfc::*;
+pub use self::deposits::*;
+pub use self::start::*;
+pub use self::atlas::*;
+pub use self::chunks::*;
+pub use self::overlay::*;
+
+/// Output of map generation
+#[derive(Debug, Clone)]
+pub struct GeneratedMap {
+    pub tiles: Vec<Tile>,
+    pub rivers: Vec<River>,
+    /// Start hex of each player; fewer than asked for if the map has too
+    /// little room
+    pub start_positions: Vec<Hex>,
+}
+
+pub fn draw_hex_grid(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    layout: Res<HexLayout>,
+    config: Res<MapGenConfig>,
+    registry: Res<TerrainRegistry>,
+    mut hex_map: ResMut<HexMap>,
+    mut start_positions: ResMut<StartPositions>,
+) {
+    info!("Generating {} map with seed {}", config.preset.name(), config.seed);
+    let GeneratedMap { tiles, rivers, start_positions: starts } = config.preset.generator().generate(&config, &registry, &layout);
+    debug!("Generated {} tiles", tiles.len());
+
+    *hex_map = HexMap::from_hexes(tiles.iter().map(|tile| &tile.hex));
+
+    for tile in tiles {
+        // Terrain tint, shaded by height
+        let [red, green, blue] = registry.get(tile.terrain).tint.unwrap_or([1.0; 3]);
+        let shade = elevation_shade(tile.elevation);
+        let color = Color::rgb(red * shade, green * shade, blue * shade);
+
+        // Tiles are drawn by their chunk's mesh, so the entity only
+        // carries game data and a position for picking
+        let entity = commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(tile.position)),
+            tile.clone(),  // The Tile component itself implements Selectable
+            Selectable::new(SelectableType::Tile),
+            BaseTint(color),
+            TileTint(color),
+        )).id();
+
+        hex_map.insert(tile.hex, HexCell::from_tile(entity, &tile));
+    }
+
+    for river in &rivers {
+        hex_map.add_river(river.edges.iter().copied());
+    }
+    spawn_river_overlays(&mut commands, &layout, &hex_map);
+    debug!("Placed {} rivers", rivers.len());
+    spawn_deposit_icons(&mut commands, &asset_server, &layout, &registry, &hex_map);
+    debug!("Placed {} deposits", hex_map.deposits().count());
+
+    debug!("Players start at {:?}", starts);
+    *start_positions = StartPositions(starts);
+}
+
+/// Brightness multiplier for a tile `elevation` above sea level: deeper
+/// water is darker and higher ground slightly lighter
+fn elevation_shade(elevation: f32) -> f32 {
+    if elevation < 0.0 {
+        (1.0 + elevation * 1.5).max(0.6)
+    } else {
+        0.85 + 0.15 * (elevation / ELEVATION_SHADE_RANGE).min(1.0)
+    }
+}
+
+/// Keep [`HexMap`] cells in step with tile entities whose data changed or
+/// that were despawned
+pub fn sync_hex_map_tiles_system(
+    tiles: Query<(Entity, &Tile), Changed<Tile>>,
+    mut removed_tiles: RemovedComponents<Tile>,
+    mut hex_map: ResMut<HexMap>,
+) {
+    for (entity, tile) in tiles.iter() {
+        // Re-insert rather than edit in place so the map's tile index
+        // follows a new entity taking over the hex
+        let Some(cell) = hex_map.get(tile.hex) else {
+            continue;
+        };
+        let occupants = cell.occupants.clone();
+        hex_map.insert(tile.hex, HexCell { occupants, ..HexCell::from_tile(entity, tile) });
+    }
+
+    for entity in removed_tiles.iter() {
+        if let Some(hex) = hex_map.tile_position(entity) {
+            hex_map.remove(hex);
+        }
+    }
+}
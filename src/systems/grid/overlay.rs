@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::sprite::MaterialMesh2dBundle;
+use crate::components::{CoordinateLabel, GridOverlayMesh, Tile};
+use crate::constants::{COORDINATE_LABELS_KEY, COORDINATE_LABEL_Z, GRID_OVERLAY_KEY, GRID_OVERLAY_Z};
+use crate::hex_map::HexMap;
+use crate::utils::{hexagon_outline_mesh, HexLayout};
+
+/// Debug overlays drawn over the map, for talking about positions.
+///
+/// [`GRID_OVERLAY_KEY`] and [`COORDINATE_LABELS_KEY`] toggle them; the
+/// overlays are redrawn whenever a setting here changes.
+#[derive(Resource, Debug, Clone)]
+pub struct GridOverlay {
+    /// Draw lines between every tile
+    pub show_grid: bool,
+    pub color: Color,
+    /// Thickness of the lines between tiles, in world units
+    pub width: f32,
+    /// Write the axial coordinates of each hex on it
+    pub show_coordinates: bool,
+}
+
+impl Default for GridOverlay {
+    fn default() -> Self {
+        Self {
+            show_grid: false,
+            color: Color::rgba(0.0, 0.0, 0.0, 0.4),
+            width: 1.5,
+            show_coordinates: false,
+        }
+    }
+}
+
+/// Flip the overlays on and off from the keyboard
+pub fn toggle_grid_overlay_system(keyboard_input: Res<Input<KeyCode>>, mut overlay: ResMut<GridOverlay>) {
+    if keyboard_input.just_pressed(GRID_OVERLAY_KEY) {
+        overlay.show_grid = !overlay.show_grid;
+    }
+    if keyboard_input.just_pressed(COORDINATE_LABELS_KEY) {
+        overlay.show_coordinates = !overlay.show_coordinates;
+    }
+}
+
+/// Keep the grid lines in step with [`GridOverlay`] and the tiles on the map
+#[allow(clippy::too_many_arguments)]
+pub fn update_grid_lines_system(
+    mut commands: Commands,
+    overlay: Res<GridOverlay>,
+    layout: Res<HexLayout>,
+    hex_map: Res<HexMap>,
+    added_tiles: Query<(), Added<Tile>>,
+    mut removed_tiles: RemovedComponents<Tile>,
+    grid_meshes: Query<Entity, With<GridOverlayMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut drawn: Local<Option<(Color, f32)>>,
+) {
+    // Drain the removals every frame, even when nothing is drawn
+    let tiles_removed = removed_tiles.iter().count() > 0;
+    let tiles_changed = tiles_removed || !added_tiles.is_empty();
+    let wanted = overlay.show_grid.then_some((overlay.color, overlay.width));
+    if wanted == *drawn && (wanted.is_none() || !tiles_changed) {
+        return;
+    }
+
+    for entity in grid_meshes.iter() {
+        commands.entity(entity).despawn();
+    }
+    *drawn = wanted;
+    let Some((color, width)) = wanted else {
+        return;
+    };
+    let Some(mesh) = grid_mesh(&layout, &hex_map, width / 2.0) else {
+        return;
+    };
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(mesh).into(),
+            material: materials.add(ColorMaterial::from(color)),
+            transform: Transform::from_xyz(0.0, 0.0, GRID_OVERLAY_Z),
+            ..default()
+        },
+        GridOverlayMesh,
+    ));
+}
+
+/// Keep a coordinate label on every hex while [`GridOverlay::show_coordinates`]
+/// is set
+#[allow(clippy::too_many_arguments)]
+pub fn update_coordinate_labels_system(
+    mut commands: Commands,
+    overlay: Res<GridOverlay>,
+    layout: Res<HexLayout>,
+    hex_map: Res<HexMap>,
+    added_tiles: Query<(), Added<Tile>>,
+    mut removed_tiles: RemovedComponents<Tile>,
+    labels: Query<Entity, With<CoordinateLabel>>,
+    mut drawn: Local<bool>,
+) {
+    // Drain the removals every frame, even when nothing is drawn
+    let tiles_removed = removed_tiles.iter().count() > 0;
+    let tiles_changed = tiles_removed || !added_tiles.is_empty();
+    if overlay.show_coordinates == *drawn && (!*drawn || !tiles_changed) {
+        return;
+    }
+
+    for entity in labels.iter() {
+        commands.entity(entity).despawn();
+    }
+    *drawn = overlay.show_coordinates;
+    if !overlay.show_coordinates {
+        return;
+    }
+
+    let style = TextStyle {
+        font_size: layout.size.x * 0.4,
+        color: Color::WHITE,
+        ..default()
+    };
+    for (hex, _) in hex_map.iter() {
+        let position = layout.hex_to_world(hex).truncate();
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(format!("{},{}", hex.q, hex.r), style.clone())
+                    .with_alignment(TextAlignment::Center),
+                // The camera is mirrored in y, so mirror the text back
+                transform: Transform::from_translation(position.extend(COORDINATE_LABEL_Z))
+                    .with_scale(Vec3::new(1.0, -1.0, 1.0)),
+                ..default()
+            },
+            CoordinateLabel { hex },
+        ));
+    }
+}
+
+/// One outline, `half_width` thick, around every hex in the map
+fn grid_mesh(layout: &HexLayout, hex_map: &HexMap, half_width: f32) -> Option<Mesh> {
+    let outline = hexagon_outline_mesh(layout, half_width);
+    let Some(VertexAttributeValues::Float32x3(offsets)) = outline.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return None;
+    };
+    let Some(Indices::U32(outline_indices)) = outline.indices() else {
+        return None;
+    };
+
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for (hex, _) in hex_map.iter() {
+        let first = positions.len() as u32;
+        let center = layout.hex_to_world(hex);
+        positions.extend(offsets.iter().map(|&[x, y, _]| [center.x + x, center.y + y, 0.0]));
+        indices.extend(outline_indices.iter().map(|index| first + index));
+    }
+    if positions.is_empty() {
+        return None;
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    Some(mesh)
+}
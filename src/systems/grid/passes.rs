@@ -0,0 +1,250 @@
+use bevy::log::warn;
+use bevy::utils::{HashMap, HashSet};
+use noise::NoiseFn;
+use crate::terrain::{TerrainFeature, TerrainId, TerrainRegistry, WaterKind};
+use crate::utils::Hex;
+use super::{choose_start_positions, compute_climate, place_deposits, smooth_terrain, trace_rivers, MapGenConfig};
+use super::generator::{MapGenContext, MapPass};
+
+/// Lays out the hexes of the configured [`crate::utils::MapShape`]
+pub struct ShapePass;
+
+impl MapPass for ShapePass {
+    fn name(&self) -> &'static str {
+        "shape"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        context.hexes = context.config.shape.hexes();
+    }
+}
+
+/// Samples the heightmap for every hex
+pub struct ElevationPass;
+
+impl MapPass for ElevationPass {
+    fn name(&self) -> &'static str {
+        "elevation"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        for &hex in &context.hexes {
+            let noise_pos = context.noise.coords(hex);
+            context.heights.insert(hex, context.noise.elevation.get(noise_pos));
+        }
+    }
+}
+
+/// Works out temperature and moisture for every land hex
+pub struct ClimatePass;
+
+impl MapPass for ClimatePass {
+    fn name(&self) -> &'static str {
+        "climate"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        let noise = &context.noise;
+        context.climate = compute_climate(
+            &context.hexes,
+            &context.heights,
+            context.config,
+            &noise.climate,
+            |hex| noise.coords(hex),
+        );
+    }
+}
+
+/// Picks the base biome of every land hex from its climate
+pub struct BiomePass;
+
+impl MapPass for BiomePass {
+    fn name(&self) -> &'static str {
+        "biomes"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        for &hex in &context.hexes {
+            // Water is placed once the land is settled
+            if let Some(climate) = context.climate.get(&hex) {
+                let biome = context.registry.biome_for(climate.temperature, climate.moisture);
+                context.terrain.insert(hex, biome);
+            }
+        }
+    }
+}
+
+/// Raises mountains from the heightmap and scatters cactus, rocks and
+/// forests over the base biomes
+pub struct FeaturePass;
+
+impl MapPass for FeaturePass {
+    fn name(&self) -> &'static str {
+        "features"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        let thresholds = &context.config.feature_thresholds;
+        for &hex in &context.hexes {
+            let Some(&biome) = context.terrain.get(&hex) else {
+                continue;
+            };
+            let height = context.heights[&hex];
+            let feature_value = context.noise.feature.get(context.noise.coords(hex));
+
+            // Mountains rise from the heightmap and override everything, then
+            // the rarest features win
+            let feature = if height > thresholds.mountain {
+                Some(TerrainFeature::Mountain)
+            } else if feature_value > thresholds.cactus {
+                Some(TerrainFeature::Cactus)
+            } else if feature_value > thresholds.rocks {
+                Some(TerrainFeature::Rocks)
+            } else if feature_value > thresholds.forest {
+                Some(TerrainFeature::Forest)
+            } else {
+                None
+            };
+            context.terrain.insert(hex, context.registry.resolve(biome, feature));
+        }
+    }
+}
+
+/// Cleans up land terrain with the per-terrain smoothing rules, see
+/// [`smooth_terrain`]
+pub struct SmoothingPass;
+
+impl MapPass for SmoothingPass {
+    fn name(&self) -> &'static str {
+        "smoothing"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        smooth_terrain(&context.hexes, &mut context.terrain, context.registry, &context.config.smoothing);
+    }
+}
+
+/// Fills everything below sea level with ocean, coast or lake
+pub struct WaterPass;
+
+impl MapPass for WaterPass {
+    fn name(&self) -> &'static str {
+        "water"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        let water = place_water(&context.hexes, &context.heights, context.config, context.registry);
+        context.terrain.extend(water);
+    }
+}
+
+/// Runs rivers downhill along tile edges
+pub struct RiverPass;
+
+impl MapPass for RiverPass {
+    fn name(&self) -> &'static str {
+        "rivers"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        context.rivers = trace_rivers(&context.hexes, &context.heights, context.config, &mut context.rng);
+    }
+}
+
+/// Places resource deposits on the finished terrain
+pub struct DepositPass;
+
+impl MapPass for DepositPass {
+    fn name(&self) -> &'static str {
+        "deposits"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        context.deposits = place_deposits(&context.hexes, &context.terrain, context.registry, &mut context.rng);
+    }
+}
+
+/// Picks a start hex for every player, see [`choose_start_positions`]
+pub struct StartPass;
+
+impl MapPass for StartPass {
+    fn name(&self) -> &'static str {
+        "start positions"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        let river_banks = context.river_banks();
+        let passable: HashSet<Hex> = context.terrain.iter()
+            .filter(|&(_, &terrain)| context.registry.is_passable(terrain))
+            .map(|(&hex, _)| hex)
+            .collect();
+        let values: HashMap<Hex, u32> = context.hexes.iter()
+            .map(|&hex| (hex, context.yields(hex, &river_banks).total()))
+            .collect();
+
+        let settings = &context.config.start;
+        context.start_positions = choose_start_positions(&context.hexes, &passable, &values, settings, &mut context.rng);
+        if context.start_positions.len() < settings.players {
+            warn!(
+                "Found room for only {} of {} players to start",
+                context.start_positions.len(), settings.players,
+            );
+        }
+    }
+}
+
+/// Water terrain for every hex below sea level.
+///
+/// Connected bodies of water that reach the map edge, or are larger than
+/// `max_lake_size`, are ocean and the rest are lakes. Ocean next to land
+/// becomes coast.
+fn place_water(
+    hexes: &[Hex],
+    heights: &HashMap<Hex, f64>,
+    config: &MapGenConfig,
+    registry: &TerrainRegistry,
+) -> HashMap<Hex, TerrainId> {
+    let is_water = |hex: &Hex| heights.get(hex).is_some_and(|&height| height < config.sea_level);
+    let is_land = |hex: &Hex| heights.get(hex).is_some_and(|&height| height >= config.sea_level);
+    let ocean = registry.water(WaterKind::Ocean);
+
+    let mut water = HashMap::new();
+    for &start in hexes {
+        if !is_water(&start) || water.contains_key(&start) {
+            continue;
+        }
+
+        // Flood fill this body of water, marking it as ocean for now
+        let mut body = vec![start];
+        let mut frontier = vec![start];
+        let mut reaches_edge = false;
+        water.insert(start, ocean);
+        while let Some(hex) = frontier.pop() {
+            for neighbor in hex.neighbors() {
+                if !heights.contains_key(&neighbor) {
+                    reaches_edge = true;
+                } else if is_water(&neighbor) && !water.contains_key(&neighbor) {
+                    water.insert(neighbor, ocean);
+                    body.push(neighbor);
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        if !reaches_edge && body.len() <= config.max_lake_size {
+            for hex in body {
+                water.insert(hex, registry.water(WaterKind::Lake));
+            }
+        }
+    }
+
+    let coast: Vec<Hex> = water.iter()
+        .filter(|&(hex, &terrain)| terrain == ocean && hex.neighbors().iter().any(is_land))
+        .map(|(&hex, _)| hex)
+        .collect();
+    for hex in coast {
+        water.insert(hex, registry.water(WaterKind::Coast));
+    }
+
+    water
+}
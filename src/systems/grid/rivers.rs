@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rand::{prelude::*, rngs::StdRng};
+use crate::components::RiverOverlay;
+use crate::constants::{RIVER_MAX_WIDTH, RIVER_WIDTH, RIVER_Z};
+use crate::hex_map::HexMap;
+use crate::utils::{Hex, HexDirection, HexEdge, HexLayout, HexVertex};
+use super::MapGenConfig;
+
+/// Settings for the hydrology pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiverSettings {
+    /// Number of rivers to try to place
+    pub count: usize,
+    /// Height above sea level a river may start from
+    pub min_source_height: f64,
+    /// Rivers shorter than this many edges are dropped
+    pub min_length: usize,
+}
+
+/// A river as a chain of hex edges, from its source down to the sea, a
+/// lake, the map border or the river it flows into (and on to that river's
+/// mouth)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct River {
+    pub edges: Vec<HexEdge>,
+}
+
+/// Trace rivers downhill along hex edges.
+///
+/// Rivers start at corners of high land hexes and always step to the lowest
+/// neighbouring corner, where a corner's height is the mean of its three
+/// hexes. A river ends when it touches water or leaves the map; one that
+/// meets an existing river follows it downstream. Rivers that get stuck in
+/// a dip are dropped.
+pub fn trace_rivers(
+    hexes: &[Hex],
+    heights: &HashMap<Hex, f64>,
+    config: &MapGenConfig,
+    rng: &mut StdRng,
+) -> Vec<River> {
+    let settings = &config.rivers;
+    let mut sources: Vec<Hex> = hexes.iter()
+        .copied()
+        .filter(|hex| heights[hex] - config.sea_level >= settings.min_source_height)
+        .collect();
+    sources.shuffle(rng);
+
+    // Next step from every corner a river already flows through
+    let mut downstream: HashMap<HexVertex, (HexVertex, HexEdge)> = HashMap::new();
+    let mut rivers = Vec::new();
+
+    for hex in sources {
+        if rivers.len() >= settings.count {
+            break;
+        }
+
+        let start = HexVertex::corner(hex, HexDirection::from_index(rng.gen_range(0..6)));
+        if downstream.contains_key(&start) {
+            continue;
+        }
+
+        let Some(steps) = trace_river(start, heights, config.sea_level, &downstream) else {
+            continue;
+        };
+        if steps.len() < settings.min_length {
+            continue;
+        }
+
+        let mut edges: Vec<HexEdge> = steps.iter().map(|&(_, _, edge)| edge).collect();
+        for &(from, to, edge) in &steps {
+            downstream.insert(from, (to, edge));
+        }
+
+        // Follow the river this one flows into, if any
+        let mut current = steps.last().map_or(start, |&(_, to, _)| to);
+        while let Some(&(next, edge)) = downstream.get(&current) {
+            edges.push(edge);
+            current = next;
+        }
+
+        rivers.push(River { edges });
+    }
+
+    rivers
+}
+
+/// Steps of a single river from `start`, or `None` if it gets stuck
+fn trace_river(
+    start: HexVertex,
+    heights: &HashMap<Hex, f64>,
+    sea_level: f64,
+    downstream: &HashMap<HexVertex, (HexVertex, HexEdge)>,
+) -> Option<Vec<(HexVertex, HexVertex, HexEdge)>> {
+    let vertex_height = |vertex: HexVertex| -> Option<f64> {
+        let [a, b, c] = vertex.hexes();
+        Some((heights.get(&a)? + heights.get(&b)? + heights.get(&c)?) / 3.0)
+    };
+    let ends_river = |vertex: HexVertex| {
+        vertex.hexes().iter().any(|hex| heights.get(hex).is_none_or(|&height| height < sea_level))
+    };
+
+    let mut steps = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = start;
+    visited.insert(current);
+
+    while !ends_river(current) && (steps.is_empty() || !downstream.contains_key(&current)) {
+        let height = vertex_height(current)?;
+        let (next, edge, next_height) = current.neighbors()
+            .into_iter()
+            .filter(|(next, _)| !visited.contains(next))
+            .filter_map(|(next, edge)| vertex_height(next).map(|h| (next, edge, h)))
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))?;
+        if next_height >= height {
+            return None;
+        }
+
+        steps.push((current, next, edge));
+        visited.insert(next);
+        current = next;
+    }
+
+    Some(steps)
+}
+
+/// Spawn a thin sprite along every river edge in the map, wider where
+/// rivers join
+pub(crate) fn spawn_river_overlays(commands: &mut Commands, layout: &HexLayout, hex_map: &HexMap) {
+    for (edge, flow) in hex_map.rivers() {
+        let (start, end) = layout.edge_to_world(edge);
+        let along = end - start;
+        let width = (RIVER_WIDTH * flow as f32).min(RIVER_MAX_WIDTH);
+        let midpoint = (start + end) / 2.0;
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(0.25, 0.5, 0.9),
+                    custom_size: Some(Vec2::new(along.length(), width)),
+                    ..default()
+                },
+                transform: Transform::from_translation(midpoint.extend(RIVER_Z))
+                    .with_rotation(Quat::from_rotation_z(along.y.atan2(along.x))),
+                ..default()
+            },
+            RiverOverlay { edge },
+        ));
+    }
+}
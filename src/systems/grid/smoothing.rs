@@ -0,0 +1,158 @@
+use bevy::utils::{HashMap, HashSet};
+use crate::terrain::{TerrainId, TerrainRegistry};
+use crate::utils::Hex;
+
+/// Settings for the cellular-automaton cleanup of land terrain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothingSettings {
+    /// Most passes over the map; smoothing stops early once nothing changes
+    pub iterations: usize,
+    /// Tiles with at least this many neighbours of other terrains take on
+    /// their most common neighbour
+    pub isolation_threshold: usize,
+}
+
+/// Clean up the terrain map with a cellular automaton.
+///
+/// Every iteration decides each tile's next terrain from the map as it was
+/// at the start of that iteration, using the first rule that applies:
+///
+/// 1. a transition of the tile's terrain whose neighbours it touches;
+/// 2. a forbidden neighbour, which swaps the tile for its most common
+///    neighbour that conflicts with nothing around it;
+/// 3. a patch smaller than its terrain's `min_cluster_size`, which takes on
+///    the terrain most common around the patch;
+/// 4. `isolation_threshold` or more neighbours of other terrains.
+///
+/// Replacements in rules 2 to 4 are skipped if they would border a terrain
+/// they conflict with, either at the start of the iteration or as already
+/// updated, so those rules never introduce a forbidden pair.
+/// Hexes without terrain (water, at this stage) are left alone.
+pub fn smooth_terrain(
+    hexes: &[Hex],
+    terrain: &mut HashMap<Hex, TerrainId>,
+    registry: &TerrainRegistry,
+    settings: &SmoothingSettings,
+) {
+    for _ in 0..settings.iterations {
+        let snapshot = terrain.clone();
+        let small_patches = small_patch_replacements(hexes, &snapshot, registry);
+
+        let mut changed = false;
+        for &hex in hexes {
+            let Some(&current) = snapshot.get(&hex) else {
+                continue;
+            };
+            let next = next_terrain(hex, current, &snapshot, terrain, &small_patches, registry, settings);
+            if next != current {
+                terrain.insert(hex, next);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+}
+
+fn next_terrain(
+    hex: Hex,
+    current: TerrainId,
+    snapshot: &HashMap<Hex, TerrainId>,
+    updated: &HashMap<Hex, TerrainId>,
+    small_patches: &HashMap<Hex, TerrainId>,
+    registry: &TerrainRegistry,
+    settings: &SmoothingSettings,
+) -> TerrainId {
+    let neighbors: Vec<TerrainId> = hex.neighbors()
+        .iter()
+        .filter_map(|neighbor| snapshot.get(neighbor).copied())
+        .collect();
+    let fits = |candidate: &TerrainId| {
+        hex.neighbors().iter()
+            .flat_map(|neighbor| [snapshot.get(neighbor), updated.get(neighbor)])
+            .flatten()
+            .all(|&neighbor| registry.can_border(*candidate, neighbor))
+    };
+    let rules = registry.smoothing(current);
+
+    let transition = rules.transitions.iter()
+        .find(|(triggers, _)| neighbors.iter().any(|neighbor| triggers.contains(neighbor)));
+    if let Some(&(_, insert)) = transition {
+        return insert;
+    }
+
+    if neighbors.iter().any(|neighbor| rules.forbidden_neighbors.contains(neighbor)) {
+        let candidates: Vec<TerrainId> = neighbors.iter().copied().filter(fits).collect();
+        return most_common(&candidates).unwrap_or(current);
+    }
+
+    if let Some(&replacement) = small_patches.get(&hex).filter(|replacement| fits(replacement)) {
+        return replacement;
+    }
+
+    let different_neighbors = neighbors.iter().filter(|&&neighbor| neighbor != current).count();
+    if different_neighbors >= settings.isolation_threshold {
+        let candidates: Vec<TerrainId> = neighbors.iter().copied().filter(fits).collect();
+        return most_common(&candidates).unwrap_or(current);
+    }
+
+    current
+}
+
+/// Most frequent terrain in the list; ties go to the earliest, so the result
+/// never depends on hash map iteration order
+fn most_common(terrains: &[TerrainId]) -> Option<TerrainId> {
+    terrains.iter()
+        .rev()
+        .max_by_key(|&&terrain| terrains.iter().filter(|&&other| other == terrain).count())
+        .copied()
+}
+
+/// Replacement terrain for every tile in a patch smaller than its terrain's
+/// `min_cluster_size`, taken from the tiles bordering the patch
+fn small_patch_replacements(
+    hexes: &[Hex],
+    snapshot: &HashMap<Hex, TerrainId>,
+    registry: &TerrainRegistry,
+) -> HashMap<Hex, TerrainId> {
+    let mut replacements = HashMap::new();
+    let mut visited = HashSet::new();
+
+    for &start in hexes {
+        let Some(&terrain) = snapshot.get(&start) else {
+            continue;
+        };
+        let min_size = registry.smoothing(terrain).min_cluster_size;
+        if min_size <= 1 || !visited.insert(start) {
+            continue;
+        }
+
+        // Flood fill the patch, collecting what surrounds it in the order
+        // it is found
+        let mut patch = vec![start];
+        let mut frontier = vec![start];
+        let mut border = Vec::new();
+        while let Some(hex) = frontier.pop() {
+            for neighbor in hex.neighbors() {
+                match snapshot.get(&neighbor) {
+                    Some(&other) if other != terrain => border.push(other),
+                    Some(_) if visited.insert(neighbor) => {
+                        patch.push(neighbor);
+                        frontier.push(neighbor);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if patch.len() < min_size {
+            if let Some(replacement) = most_common(&border) {
+                replacements.extend(patch.into_iter().map(|hex| (hex, replacement)));
+            }
+        }
+    }
+
+    replacements
+}
@@ -0,0 +1,220 @@
+use bevy::utils::{HashMap, HashSet};
+use rand::{prelude::*, rngs::StdRng};
+use crate::utils::{range, Hex};
+
+/// Settings for choosing where players start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StartSettings {
+    /// Number of players to find start hexes for
+    pub players: usize,
+    /// Passable hexes a start needs within one step, its own included, so
+    /// every starting unit gets a hex of its own; defaults to the number of
+    /// [`STARTING_UNITS`](crate::constants::STARTING_UNITS)
+    pub units_per_player: usize,
+    /// Hexes within this many steps of a start count towards its resources
+    pub area_radius: i32,
+    /// Layouts tried from different first starts; the best is kept
+    pub attempts: usize,
+    /// Hexes of distance between starts that one point of difference in
+    /// their resources is worth
+    pub fairness: f32,
+}
+
+/// Start hexes for up to `settings.players` players.
+///
+/// A hex can be a start if it is passable, has room for every starting
+/// unit next to it and its area is worth at least the median of such hexes,
+/// where an area's worth is the sum of `values` within `area_radius`. Starts
+/// share the largest landmass that has enough candidates, so players can
+/// reach each other, falling back to the whole map.
+///
+/// Each attempt begins at a random candidate and keeps adding the one that
+/// lies farthest from the starts so far while being worth about as much as
+/// the first. The layout whose closest pair is farthest apart, less
+/// `fairness` times the spread in worth, wins.
+pub fn choose_start_positions(
+    hexes: &[Hex],
+    passable: &HashSet<Hex>,
+    values: &HashMap<Hex, u32>,
+    settings: &StartSettings,
+    rng: &mut StdRng,
+) -> Vec<Hex> {
+    if settings.players == 0 {
+        return Vec::new();
+    }
+
+    let worth = |hex: Hex| -> u32 {
+        range(hex, settings.area_radius)
+            .filter_map(|hex| values.get(&hex))
+            .sum()
+    };
+    let mut candidates: Vec<(Hex, u32)> = hexes.iter()
+        .copied()
+        .filter(|hex| passable.contains(hex))
+        .filter(|hex| {
+            let room = 1 + hex.neighbors().iter().filter(|neighbor| passable.contains(*neighbor)).count();
+            room >= settings.units_per_player
+        })
+        .map(|hex| (hex, worth(hex)))
+        .collect();
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+
+    let mut worths: Vec<u32> = candidates.iter().map(|&(_, worth)| worth).collect();
+    worths.sort_unstable();
+    let median = worths[worths.len() / 2];
+    candidates.retain(|&(_, worth)| worth >= median);
+
+    let landmasses = landmasses(hexes, passable);
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for &(hex, _) in &candidates {
+        *sizes.entry(landmasses[&hex]).or_default() += 1;
+    }
+    let largest = sizes.iter()
+        .filter(|&(_, &count)| count >= settings.players)
+        .max_by_key(|&(&landmass, &count)| (count, std::cmp::Reverse(landmass)))
+        .map(|(&landmass, _)| landmass);
+    if let Some(largest) = largest {
+        candidates.retain(|(hex, _)| landmasses[hex] == largest);
+    }
+
+    let mut best: Option<(f32, Vec<(Hex, u32)>)> = None;
+    for _ in 0..settings.attempts.max(1) {
+        let Some(&first) = candidates.choose(rng) else {
+            break;
+        };
+        let mut starts = vec![first];
+        while starts.len() < settings.players {
+            let next = candidates.iter()
+                .filter(|(hex, _)| starts.iter().all(|(start, _)| start != hex))
+                .map(|&(hex, worth)| {
+                    let spacing = starts.iter().map(|(start, _)| start.distance(hex)).min().unwrap_or(0);
+                    let score = spacing as f32 - settings.fairness * worth.abs_diff(first.1) as f32;
+                    ((hex, worth), score)
+                })
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(candidate, _)| candidate);
+            match next {
+                Some(next) => starts.push(next),
+                None => break,
+            }
+        }
+
+        let score = layout_score(&starts, settings.fairness);
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, starts));
+        }
+    }
+
+    best.map(|(_, starts)| starts.into_iter().map(|(hex, _)| hex).collect())
+        .unwrap_or_default()
+}
+
+/// Distance between the closest pair of starts, less `fairness` times the
+/// gap between the richest and poorest start
+fn layout_score(starts: &[(Hex, u32)], fairness: f32) -> f32 {
+    let spacing = starts.iter()
+        .enumerate()
+        .flat_map(|(index, &(a, _))| starts[index + 1..].iter().map(move |&(b, _)| a.distance(b)))
+        .min()
+        .unwrap_or(0);
+    let (poorest, richest) = starts.iter()
+        .fold((u32::MAX, 0), |(poorest, richest), &(_, worth)| (poorest.min(worth), richest.max(worth)));
+    spacing as f32 - fairness * richest.saturating_sub(poorest) as f32
+}
+
+/// Index of the connected passable region each passable hex belongs to
+fn landmasses(hexes: &[Hex], passable: &HashSet<Hex>) -> HashMap<Hex, usize> {
+    let mut landmass = HashMap::new();
+    let mut count = 0;
+    for &start in hexes {
+        if !passable.contains(&start) || landmass.contains_key(&start) {
+            continue;
+        }
+
+        landmass.insert(start, count);
+        let mut frontier = vec![start];
+        while let Some(hex) = frontier.pop() {
+            for neighbor in hex.neighbors() {
+                if passable.contains(&neighbor) && !landmass.contains_key(&neighbor) {
+                    landmass.insert(neighbor, count);
+                    frontier.push(neighbor);
+                }
+            }
+        }
+        count += 1;
+    }
+    landmass
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::spiral;
+
+    fn settings(players: usize) -> StartSettings {
+        StartSettings { players, units_per_player: 3, area_radius: 2, attempts: 5, fairness: 0.5 }
+    }
+
+    fn values(passable: &HashSet<Hex>) -> HashMap<Hex, u32> {
+        passable.iter().map(|&hex| (hex, 1)).collect()
+    }
+
+    #[test]
+    fn no_players_get_no_starts() {
+        let hexes: Vec<Hex> = spiral(Hex::ZERO, 4).collect();
+        let passable: HashSet<Hex> = hexes.iter().copied().collect();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let starts = choose_start_positions(&hexes, &passable, &values(&passable), &settings(0), &mut rng);
+        assert!(starts.is_empty());
+    }
+
+    #[test]
+    fn starts_are_passable_with_room_for_every_unit() {
+        // Scatter impassable hexes so some would-be starts lack room
+        let hexes: Vec<Hex> = spiral(Hex::ZERO, 8).collect();
+        let passable: HashSet<Hex> = hexes.iter()
+            .copied()
+            .filter(|hex| (hex.q * 7 + hex.r * 3).rem_euclid(5) != 0)
+            .collect();
+        let settings = StartSettings { units_per_player: 5, ..settings(4) };
+        let mut rng = StdRng::seed_from_u64(2);
+
+        let starts = choose_start_positions(&hexes, &passable, &values(&passable), &settings, &mut rng);
+        assert_eq!(starts.len(), 4);
+        for start in starts {
+            assert!(passable.contains(&start), "{} is impassable", start);
+            let room = 1 + start.neighbors().iter().filter(|neighbor| passable.contains(*neighbor)).count();
+            assert!(room >= settings.units_per_player, "{} only has room for {} units", start, room);
+        }
+    }
+
+    #[test]
+    fn starts_share_the_largest_landmass() {
+        let west = Hex::new(-10, 0);
+        let east = Hex::new(10, 0);
+        let hexes: Vec<Hex> = spiral(Hex::ZERO, 15).collect();
+        let passable: HashSet<Hex> = spiral(west, 4).chain(spiral(east, 2)).collect();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let starts = choose_start_positions(&hexes, &passable, &values(&passable), &settings(3), &mut rng);
+        assert_eq!(starts.len(), 3);
+        for start in starts {
+            assert!(start.distance(west) <= 4, "{} is off the western landmass", start);
+        }
+    }
+
+    #[test]
+    fn too_few_candidates_give_fewer_starts() {
+        let hexes: Vec<Hex> = spiral(Hex::ZERO, 3).collect();
+        let passable: HashSet<Hex> = [Hex::ZERO, Hex::new(3, 0)].into_iter().collect();
+        let settings = StartSettings { units_per_player: 1, ..settings(5) };
+        let mut rng = StdRng::seed_from_u64(4);
+
+        let mut starts = choose_start_positions(&hexes, &passable, &values(&passable), &settings, &mut rng);
+        starts.sort_by_key(|hex| (hex.q, hex.r));
+        assert_eq!(starts, vec![Hex::ZERO, Hex::new(3, 0)]);
+    }
+}
@@ -0,0 +1,344 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use bevy::log::warn;
+use bevy::utils::HashMap;
+use rand::{prelude::*, rngs::StdRng};
+use crate::terrain::{TerrainId, TerrainRegistry, WfcRules};
+use crate::utils::{Hex, HexLayout};
+use super::{GeneratedMap, MapGenConfig};
+use super::generator::{MapGenContext, MapGenerator, MapPass, PassPipeline};
+use super::passes::{DepositPass, ShapePass, StartPass};
+
+/// Settings for wave function collapse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WfcSettings {
+    /// Decisions one attempt may undo before it starts over
+    pub max_backtracks: usize,
+    /// Attempts before giving up on a region
+    pub attempts: usize,
+}
+
+/// Part of the map filled by a [`WfcPass`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WfcRegion {
+    /// Every hex in the map
+    Map,
+    /// Hexes within `radius` steps of `center`
+    Area { center: Hex, radius: i32 },
+}
+
+impl WfcRegion {
+    pub fn contains(self, hex: Hex) -> bool {
+        match self {
+            WfcRegion::Map => true,
+            WfcRegion::Area { center, radius } => hex.distance(center) <= radius,
+        }
+    }
+}
+
+/// Fills a region with terrain from the hand-authored adjacency rules in the
+/// terrain definitions, see [`crate::terrain::WfcDefinitions`].
+///
+/// Terrain already placed around the region constrains the hexes on its
+/// border, so a region blends into the map it is carved out of.
+pub struct WfcPass {
+    pub region: WfcRegion,
+}
+
+impl MapPass for WfcPass {
+    fn name(&self) -> &'static str {
+        "wave function collapse"
+    }
+
+    fn run(&self, context: &mut MapGenContext) {
+        let Some(rules) = context.registry.wfc_rules() else {
+            warn!("Terrain definitions have no wave function collapse rules, skipping");
+            return;
+        };
+
+        let region: Vec<Hex> = context.hexes.iter()
+            .copied()
+            .filter(|&hex| self.region.contains(hex))
+            .collect();
+        match collapse(&region, &context.terrain, rules, &context.config.wfc, &mut context.rng) {
+            Some(terrain) => context.terrain.extend(terrain),
+            None => warn!(
+                "Wave function collapse found no valid terrain in {} attempts, leaving the region unchanged",
+                context.config.wfc.attempts,
+            ),
+        }
+    }
+}
+
+/// Generates the whole map by wave function collapse instead of noise
+pub struct WfcGenerator;
+
+impl MapGenerator for WfcGenerator {
+    fn generate(&self, config: &MapGenConfig, registry: &TerrainRegistry, layout: &HexLayout) -> GeneratedMap {
+        PassPipeline::new()
+            .with_pass(ShapePass)
+            .with_pass(WfcPass { region: WfcRegion::Map })
+            .with_pass(DepositPass)
+            .with_pass(StartPass)
+            .generate(config, registry, layout)
+    }
+}
+
+/// Terrain for every hex in `region` such that every pair of neighbours is
+/// allowed by `rules`.
+///
+/// Hexes outside the region with terrain in `fixed` count as neighbours
+/// too, unless their terrain is not one of the rules' tiles. Each attempt
+/// collapses the hex with the fewest options left, picking a tile by weight,
+/// and backtracks when that leaves a hex with no options. Returns `None`
+/// once every attempt has run out of backtracks.
+pub fn collapse(
+    region: &[Hex],
+    fixed: &HashMap<Hex, TerrainId>,
+    rules: &WfcRules,
+    settings: &WfcSettings,
+    rng: &mut StdRng,
+) -> Option<HashMap<Hex, TerrainId>> {
+    let index: HashMap<Hex, usize> = region.iter()
+        .enumerate()
+        .map(|(index, &hex)| (hex, index))
+        .collect();
+    let neighbors: Vec<Vec<usize>> = region.iter()
+        .map(|hex| hex.neighbors().iter().filter_map(|neighbor| index.get(neighbor).copied()).collect())
+        .collect();
+    let initial: Vec<u64> = region.iter()
+        .map(|hex| {
+            hex.neighbors()
+                .iter()
+                .filter(|neighbor| !index.contains_key(*neighbor))
+                .filter_map(|neighbor| fixed.get(neighbor).and_then(|&terrain| rules.tile(terrain)))
+                .fold(rules.all(), |options, tile| options & rules.neighbors(tile))
+        })
+        .collect();
+
+    for _ in 0..settings.attempts {
+        let mut wave = Wave {
+            rules,
+            neighbors: &neighbors,
+            options: initial.clone(),
+            trail: Vec::new(),
+            queue: BinaryHeap::new(),
+        };
+        if let Some(tiles) = wave.run(settings.max_backtracks, rng) {
+            return Some(region.iter()
+                .zip(tiles)
+                .map(|(&hex, tile)| (hex, rules.terrains[tile]))
+                .collect());
+        }
+    }
+
+    None
+}
+
+/// State of one collapse attempt, with hexes addressed by their position in
+/// the region and tile sets as masks over [`WfcRules::terrains`]
+struct Wave<'a> {
+    rules: &'a WfcRules,
+    neighbors: &'a [Vec<usize>],
+    /// Tiles each hex may still become
+    options: Vec<u64>,
+    /// Previous options of every change, so decisions can be undone
+    trail: Vec<(usize, u64)>,
+    /// Undecided hexes, fewest options first with random tie-breaks;
+    /// entries go stale when a hex's options change and are skipped
+    queue: BinaryHeap<(Reverse<u32>, u64, usize)>,
+}
+
+impl Wave<'_> {
+    /// Chosen tile for every hex, or `None` on a contradiction that
+    /// `max_backtracks` undone decisions could not resolve
+    fn run(&mut self, max_backtracks: usize, rng: &mut StdRng) -> Option<Vec<usize>> {
+        let all: Vec<usize> = (0..self.options.len()).collect();
+        if self.options.contains(&0) || !self.propagate(all.clone(), rng) {
+            return None;
+        }
+        for hex in all {
+            self.enqueue(hex, rng);
+        }
+
+        // Each decision is the hex, the tile picked for it and the trail
+        // length before it was made
+        let mut decisions: Vec<(usize, usize, usize)> = Vec::new();
+        let mut backtracks = 0;
+        while let Some(hex) = self.next_undecided() {
+            let tile = self.pick(hex, rng);
+            decisions.push((hex, tile, self.trail.len()));
+            self.restrict(hex, 1 << tile, rng);
+
+            let mut consistent = self.propagate(vec![hex], rng);
+            while !consistent {
+                // Undo the latest decision and rule out the tile it picked
+                let (hex, tile, trail_len) = decisions.pop()?;
+                backtracks += 1;
+                if backtracks > max_backtracks {
+                    return None;
+                }
+                self.undo(trail_len, rng);
+
+                let remaining = self.options[hex] & !(1 << tile);
+                consistent = remaining != 0 && {
+                    self.restrict(hex, remaining, rng);
+                    self.propagate(vec![hex], rng)
+                };
+            }
+        }
+
+        Some(self.options.iter().map(|options| options.trailing_zeros() as usize).collect())
+    }
+
+    /// Narrow the options of a hex, remembering the old ones
+    fn restrict(&mut self, hex: usize, options: u64, rng: &mut StdRng) {
+        self.trail.push((hex, self.options[hex]));
+        self.options[hex] = options;
+        self.enqueue(hex, rng);
+    }
+
+    /// Roll back every change made after the trail was `trail_len` long
+    fn undo(&mut self, trail_len: usize, rng: &mut StdRng) {
+        for (hex, options) in self.trail.split_off(trail_len).into_iter().rev() {
+            self.options[hex] = options;
+            self.enqueue(hex, rng);
+        }
+    }
+
+    fn enqueue(&mut self, hex: usize, rng: &mut StdRng) {
+        let options = self.options[hex];
+        if options.count_ones() > 1 {
+            let (total, weighted_log) = tiles(options)
+                .map(|tile| self.rules.weights[tile])
+                .fold((0.0, 0.0), |(total, weighted_log), weight| (total + weight, weighted_log + weight * weight.ln()));
+            let entropy = total.ln() - weighted_log / total + rng.gen_range(0.0..1e-4);
+            self.queue.push((Reverse(entropy.max(0.0).to_bits()), options, hex));
+        }
+    }
+
+    fn next_undecided(&mut self) -> Option<usize> {
+        while let Some((_, options, hex)) = self.queue.pop() {
+            if self.options[hex] == options {
+                return Some(hex);
+            }
+        }
+        None
+    }
+
+    /// Remove options that no neighbour allows, spreading out from `changed`;
+    /// false if some hex is left with none
+    fn propagate(&mut self, mut changed: Vec<usize>, rng: &mut StdRng) -> bool {
+        while let Some(hex) = changed.pop() {
+            let allowed = tiles(self.options[hex]).fold(0, |allowed, tile| allowed | self.rules.neighbors(tile));
+            for &neighbor in &self.neighbors[hex] {
+                let options = self.options[neighbor] & allowed;
+                if options == self.options[neighbor] {
+                    continue;
+                }
+                if options == 0 {
+                    return false;
+                }
+                self.restrict(neighbor, options, rng);
+                changed.push(neighbor);
+            }
+        }
+        true
+    }
+
+    /// One of the hex's remaining tiles, chosen by weight
+    fn pick(&self, hex: usize, rng: &mut StdRng) -> usize {
+        let options = self.options[hex];
+        let total: f32 = tiles(options).map(|tile| self.rules.weights[tile]).sum();
+        let mut roll = rng.gen_range(0.0..total);
+        for tile in tiles(options) {
+            let weight = self.rules.weights[tile];
+            if roll < weight {
+                return tile;
+            }
+            roll -= weight;
+        }
+        // Rounding can leave the roll just past the last weight
+        tiles(options).last().unwrap_or(0)
+    }
+}
+
+/// Tiles in a mask, lowest first
+fn tiles(mask: u64) -> impl Iterator<Item = usize> {
+    (0..WfcRules::MAX_TILES).filter(move |&tile| mask & (1 << tile) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{TerrainDefinitions, WfcDefinitions, WfcTileDef, SHIPPED_DEFINITIONS};
+    use crate::utils::{ring, spiral};
+
+    const SETTINGS: WfcSettings = WfcSettings { max_backtracks: 1000, attempts: 3 };
+
+    fn shipped_registry() -> TerrainRegistry {
+        TerrainRegistry::from_ron(SHIPPED_DEFINITIONS).unwrap()
+    }
+
+    /// Whether `rules` allow terrains `a` and `b` side by side
+    fn allowed(rules: &WfcRules, a: TerrainId, b: TerrainId) -> bool {
+        let (Some(a), Some(b)) = (rules.tile(a), rules.tile(b)) else {
+            return false;
+        };
+        rules.neighbors(a) & (1 << b) != 0
+    }
+
+    #[test]
+    fn collapse_only_places_allowed_neighbors() {
+        let registry = shipped_registry();
+        let rules = registry.wfc_rules().unwrap();
+        let region: Vec<Hex> = spiral(Hex::ZERO, 6).collect();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let terrain = collapse(&region, &HashMap::new(), rules, &SETTINGS, &mut rng).unwrap();
+        assert_eq!(terrain.len(), region.len());
+        for &hex in &region {
+            for neighbor in hex.neighbors() {
+                if let Some(&other) = terrain.get(&neighbor) {
+                    assert!(allowed(rules, terrain[&hex], other), "{} next to {}", hex, neighbor);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn collapse_respects_fixed_terrain_around_the_region() {
+        let registry = shipped_registry();
+        let rules = registry.wfc_rules().unwrap();
+        let ocean = registry.id("ocean").unwrap();
+        let region: Vec<Hex> = spiral(Hex::ZERO, 3).collect();
+        let fixed: HashMap<Hex, TerrainId> = ring(Hex::ZERO, 4).map(|hex| (hex, ocean)).collect();
+        let mut rng = StdRng::seed_from_u64(2);
+
+        let terrain = collapse(&region, &fixed, rules, &SETTINGS, &mut rng).unwrap();
+        for hex in ring(Hex::ZERO, 3) {
+            assert!(allowed(rules, terrain[&hex], ocean), "{} can't border the ocean around it", hex);
+        }
+    }
+
+    #[test]
+    fn collapse_gives_up_on_unsatisfiable_rules() {
+        // Grass and sand may only border each other, which no three hexes
+        // meeting at a corner can satisfy
+        let mut definitions = TerrainDefinitions::from_ron(SHIPPED_DEFINITIONS).unwrap();
+        let tile = |terrain: &str, neighbor: &str| WfcTileDef {
+            terrain: terrain.to_string(),
+            weight: 1.0,
+            neighbors: vec![neighbor.to_string()],
+        };
+        definitions.wfc = Some(WfcDefinitions {
+            tiles: vec![tile("grass", "sand"), tile("sand", "grass")],
+        });
+        let registry = TerrainRegistry::new(definitions).unwrap();
+        let region: Vec<Hex> = spiral(Hex::ZERO, 2).collect();
+        let settings = WfcSettings { max_backtracks: 50, attempts: 2 };
+        let mut rng = StdRng::seed_from_u64(3);
+
+        assert_eq!(collapse(&region, &HashMap::new(), registry.wfc_rules().unwrap(), &settings, &mut rng), None);
+    }
+}
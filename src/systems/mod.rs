@@ -6,8 +6,10 @@ pub mod camera;
 pub mod selection;
 // Entity management systems
 pub mod entity;
+// Planned path drawn under the cursor for the selected unit
+pub mod path_preview;
 // Grid-related systems
 pub mod grid;
 
 // Re-export all systems
-pub use self::{camera::*, selection::*, entity::*, grid::*};
\ No newline at end of file
+pub use self::{camera::*, selection::*, entity::*, path_preview::*, grid::*};
\ No newline at end of file
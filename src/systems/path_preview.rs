@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::components::PathMarker;
+use crate::constants::PATH_PREVIEW_Z;
+use crate::pathfinding::HexPath;
+use crate::utils::{Hex, HexLayout};
+
+use super::{cursor_to_hex, MainCamera, MovementRange, UnitMovement};
+
+/// Colour of the steps the unit can take this turn
+const THIS_TURN_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.9);
+/// Colour of the steps left for later turns
+const LATER_TURN_COLOR: Color = Color::rgba(1.0, 0.65, 0.2, 0.9);
+/// Thickness of the line joining the steps, in world units
+const PATH_LINE_WIDTH: f32 = 3.0;
+/// Size of the dot on each step and on the goal
+const STEP_DOT_SIZE: f32 = 7.0;
+const GOAL_DOT_SIZE: f32 = 12.0;
+
+/// Path the selected unit would take to the hex under the cursor
+#[derive(Resource, Debug, Default)]
+pub struct PathPreview {
+    /// Unit the path is planned for
+    pub unit: Option<Entity>,
+    /// Hex under the cursor when the path was planned
+    pub target: Option<Hex>,
+    /// `None` if there is no unit, no hex or no way there
+    pub path: Option<HexPath>,
+}
+
+/// Plan a path from the selected unit to the hex under the cursor and draw
+/// it as a line of dots, marking the turn each step is reached on when it
+/// takes more than one and the total cost at the goal
+#[allow(clippy::too_many_arguments)]
+pub fn update_path_preview_system(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    layout: Res<HexLayout>,
+    range: Res<MovementRange>,
+    movement: UnitMovement,
+    markers: Query<Entity, With<PathMarker>>,
+    mut preview: ResMut<PathPreview>,
+) {
+    let cursor_hex = windows.get_single().ok()
+        .and_then(|window| window.cursor_position())
+        .zip(camera_q.get_single().ok())
+        .and_then(|(cursor, (camera, camera_transform))| cursor_to_hex(camera, camera_transform, &layout, cursor));
+    // Searching is only worth it when the unit, the cursor or the map
+    // around the unit changed
+    if preview.unit == range.unit && preview.target == cursor_hex && !range.is_changed() {
+        return;
+    }
+
+    let path = range.unit.zip(cursor_hex)
+        .and_then(|(unit, hex)| movement.path(unit, hex))
+        .filter(|path| !path.is_empty());
+    preview.unit = range.unit;
+    preview.target = cursor_hex;
+    if path == preview.path {
+        return;
+    }
+    preview.path = path;
+
+    for entity in markers.iter() {
+        commands.entity(entity).despawn();
+    }
+    let (Some(unit), Some(path)) = (preview.unit, preview.path.as_ref()) else {
+        return;
+    };
+
+    let turns = path.turns(movement.movement_points(unit).unwrap_or(0));
+    let several_turns = turns.last().is_some_and(|&turn| turn > 1);
+    let label_offset = Vec2::new(0.0, layout.size.y * 0.45);
+    let mut previous = layout.hex_to_world(path.start()).truncate();
+    for ((hex, _), &turn) in path.steps().zip(&turns) {
+        let position = layout.hex_to_world(hex).truncate();
+        let color = if turn == 1 { THIS_TURN_COLOR } else { LATER_TURN_COLOR };
+        let dot_size = if hex == path.goal() { GOAL_DOT_SIZE } else { STEP_DOT_SIZE };
+
+        let along = position - previous;
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(along.length(), PATH_LINE_WIDTH)),
+                    ..default()
+                },
+                transform: Transform::from_translation(((previous + position) / 2.0).extend(PATH_PREVIEW_Z))
+                    .with_rotation(Quat::from_rotation_z(along.y.atan2(along.x))),
+                ..default()
+            },
+            PathMarker,
+        ));
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(dot_size)),
+                    ..default()
+                },
+                // A square turned on its corner
+                transform: Transform::from_translation(position.extend(PATH_PREVIEW_Z))
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..default()
+            },
+            PathMarker,
+        ));
+        if several_turns {
+            spawn_path_label(&mut commands, &layout, position + label_offset, turn.to_string(), color);
+        }
+        previous = position;
+    }
+
+    let goal = layout.hex_to_world(path.goal()).truncate();
+    let color = if several_turns { LATER_TURN_COLOR } else { THIS_TURN_COLOR };
+    spawn_path_label(&mut commands, &layout, goal - label_offset, format!("{} MP", path.total_cost()), color);
+}
+
+fn spawn_path_label(commands: &mut Commands, layout: &HexLayout, position: Vec2, text: String, color: Color) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(text, TextStyle {
+                font_size: layout.size.x * 0.5,
+                color,
+                ..default()
+            }),
+            // The camera is mirrored in y, so mirror the text back
+            transform: Transform::from_translation(position.extend(PATH_PREVIEW_Z + 0.01))
+                .with_scale(Vec3::new(1.0, -1.0, 1.0)),
+            ..default()
+        },
+        PathMarker,
+    ));
+}
@@ -1,9 +1,12 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy::window::PrimaryWindow;
-use crate::components::{Selectable, SelectableType};
+use crate::components::{BaseTint, Selectable, SelectableType, Tile, TileTint, Unit, UnitState};
+use crate::constants::selection::{RANGE_GREY_SHADE, RANGE_GREY_WEIGHTS, RANGE_LIGHTEN};
 use crate::resources::MouseState;
+use crate::utils::{Hex, HexLayout};
 
-use super::MainCamera;
+use super::{cursor_to_hex, update_path_preview_system, MainCamera, PathPreview, UnitCommand, UnitCommandType, UnitMovement};
 
 // Resource to track selection state
 #[derive(Resource, Default)]
@@ -13,6 +16,15 @@ pub struct SelectionState {
     pub selection_type: Option<SelectableType>,
 }
 
+/// Hexes the selected unit can reach this turn, shown by tinting tiles
+#[derive(Resource, Debug, Default)]
+pub struct MovementRange {
+    /// Unit the range belongs to; `None` unless an idle unit is selected
+    pub unit: Option<Entity>,
+    /// Movement points needed to reach each hex
+    pub reachable: HashMap<Hex, u32>,
+}
+
 // Plugin to register all selection-related systems
 pub struct SelectionPlugin;
 
@@ -21,10 +33,14 @@ impl Plugin for SelectionPlugin {
         app
             .init_resource::<SelectionState>()
             .init_resource::<MouseState>()
+            .init_resource::<MovementRange>()
+            .init_resource::<PathPreview>()
             .add_systems(Update, (
                 selection_hover_system,
                 selection_click_system,
+                movement_range_system.before(selection_highlight_system),
                 selection_highlight_system,
+                update_path_preview_system.after(movement_range_system),
             ));
     }
 }
@@ -33,11 +49,13 @@ impl Plugin for SelectionPlugin {
 fn selection_hover_system(
     windows: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut selectables_query: Query<(Entity, &GlobalTransform, &mut Selectable)>,
+    mut selectables_query: Query<(Entity, &GlobalTransform, &mut Selectable, Option<&Tile>)>,
     mut selection_state: ResMut<SelectionState>,
+    layout: Res<HexLayout>,
 ) {
     // Reset previous hover state
     let mut closest_entity = None;
+    let mut tile_under_cursor = None;
     let mut closest_distance = f32::MAX;
     let mut closest_depth = f32::MAX;
 
@@ -53,8 +71,18 @@ fn selection_hover_system(
     };
 
     if let Some(cursor_position) = window.cursor_position() {
+        let cursor_hex = cursor_to_hex(camera, camera_transform, &layout, cursor_position);
+
         // Loop through all selectables to find what's under the cursor
-        for (entity, transform, _selectable) in selectables_query.iter_mut() {
+        for (entity, transform, _selectable, tile) in selectables_query.iter_mut() {
+            // Tiles are picked by hex math rather than distance to their centre
+            if let Some(tile) = tile {
+                if Some(tile.hex) == cursor_hex {
+                    tile_under_cursor = Some(entity);
+                }
+                continue;
+            }
+
             // Project entity position to screen space
             let entity_position = transform.translation();
             
@@ -74,8 +102,11 @@ fn selection_hover_system(
         }
     }
 
+    // Units and buildings take priority over the tile beneath them
+    let closest_entity = closest_entity.or(tile_under_cursor);
+
     // Update hover state
-    for (entity, _, mut selectable) in selectables_query.iter_mut() {
+    for (entity, _, mut selectable, _) in selectables_query.iter_mut() {
         selectable.is_hovered = Some(entity) == closest_entity;
         
         if selectable.is_hovered && selection_state.hovered_entity != Some(entity) {
@@ -89,14 +120,19 @@ fn selection_hover_system(
     }
 }
 
-// System that handles selection when clicking on entities
+// System that handles selection when clicking on entities. With a unit
+// selected, clicking a hex it has a path to moves it there instead.
+#[allow(clippy::too_many_arguments)]
 fn selection_click_system(
     mouse_buttons: Res<Input<MouseButton>>,
     mut mouse_state: ResMut<MouseState>,
     windows: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut selectables_query: Query<(Entity, &GlobalTransform, &mut Selectable)>,
+    mut selectables_query: Query<(Entity, &GlobalTransform, &mut Selectable, Option<&Tile>)>,
     mut selection_state: ResMut<SelectionState>,
+    layout: Res<HexLayout>,
+    preview: Res<PathPreview>,
+    mut unit_commands: EventWriter<UnitCommand>,
 ) {
     let window = match windows.get_single() {
         Ok(win) => win,
@@ -126,39 +162,75 @@ fn selection_click_system(
             let mut closest_distance = f32::MAX;
             let mut closest_depth = f32::MAX;
             let mut selection_type = None;
+            let mut tile_under_cursor = None;
 
-            // Raycast from cursor position to world
-            if let Some(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-                // Look for the closest entity to our cursor
-                for (entity, transform, selectable) in selectables_query.iter_mut() {
-                    let entity_position = transform.translation();
+            // Raycast from cursor position to world; units are picked on
+            // screen, so they stay clickable where the ray misses the map
+            let cursor_hex = cursor_to_hex(camera, camera_transform, &layout, cursor_position);
+
+            // Look for the closest entity to our cursor
+            for (entity, transform, selectable, tile) in selectables_query.iter_mut() {
+                // Tiles are picked by hex math rather than distance to their centre
+                if let Some(tile) = tile {
+                    if Some(tile.hex) == cursor_hex {
+                        tile_under_cursor = Some(entity);
+                    }
+                    continue;
+                }
+
+                let entity_position = transform.translation();
+                
+                // Project entity to screen space
+                if let Some(screen_pos) = camera.world_to_viewport(camera_transform, entity_position) {
+                    let distance = screen_pos.distance(cursor_position);
+                    
+                    // Use selection radius based on the type of selectable
+                    let selection_radius = match selectable.selectable_type {
+                        SelectableType::Unit => 40.0,
+                        SelectableType::Tile => 30.0,
+                        SelectableType::Building => 35.0,
+                        SelectableType::UI => 15.0,
+                    };
                     
-                    // Project entity to screen space
-                    if let Some(screen_pos) = camera.world_to_viewport(camera_transform, entity_position) {
-                        let distance = screen_pos.distance(cursor_position);
-                        
-                        // Use selection radius based on the type of selectable
-                        let selection_radius = match selectable.selectable_type {
-                            SelectableType::Unit => 40.0,
-                            SelectableType::Tile => 30.0,
-                            SelectableType::Building => 35.0,
-                            SelectableType::UI => 15.0,
-                        };
-                        
-                        if distance < selection_radius && 
-                           (distance < closest_distance || 
-                            (distance == closest_distance && entity_position.z < closest_depth)) {
-                            closest_entity = Some(entity);
-                            closest_distance = distance;
-                            closest_depth = entity_position.z;
-                            selection_type = Some(selectable.selectable_type);
-                        }
+                    if distance < selection_radius && 
+                       (distance < closest_distance || 
+                        (distance == closest_distance && entity_position.z < closest_depth)) {
+                        closest_entity = Some(entity);
+                        closest_distance = distance;
+                        closest_depth = entity_position.z;
+                        selection_type = Some(selectable.selectable_type);
                     }
                 }
             }
 
+            // A click on open ground the selected unit has a path to is a
+            // move order; the unit stays selected
+            let move_order = preview.unit
+                .zip(preview.path.as_ref())
+                .filter(|&(unit, path)| {
+                    closest_entity.is_none()
+                        && selection_state.selected_entity == Some(unit)
+                        && preview.target == Some(path.goal())
+                });
+            if let Some((unit, path)) = move_order {
+                unit_commands.send(UnitCommand {
+                    unit,
+                    command_type: UnitCommandType::MoveTo(path.goal()),
+                });
+                closest_entity = Some(unit);
+                selection_type = selection_state.selection_type;
+            }
+
+            // Units and buildings take priority over the tile beneath them
+            if closest_entity.is_none() {
+                if let Some(tile_entity) = tile_under_cursor {
+                    closest_entity = Some(tile_entity);
+                    selection_type = Some(SelectableType::Tile);
+                }
+            }
+
             // Update selection state for all entities
-            for (entity, _, mut selectable) in selectables_query.iter_mut() {
+            for (entity, _, mut selectable, _) in selectables_query.iter_mut() {
                 // Only select the closest entity
                 let is_selected = Some(entity) == closest_entity;
                 selectable.is_selected = is_selected;
@@ -185,21 +257,72 @@ fn selection_click_system(
     }
 }
 
-// Updated system to apply highlighting directly to the entity's sprite
+/// Keep [`MovementRange`] up to date for the selected unit while it stands
+/// still
+fn movement_range_system(
+    selection_state: Res<SelectionState>,
+    units: Query<&UnitState, With<Unit>>,
+    movement: UnitMovement,
+    mut range: ResMut<MovementRange>,
+) {
+    let unit = selection_state.selected_entity
+        .filter(|&entity| units.get(entity).is_ok_and(|state| !state.is_moving));
+    let reachable = unit.map(|unit| movement.reachable(unit)).unwrap_or_default();
+    if range.unit != unit || range.reachable != reachable {
+        range.unit = unit;
+        range.reachable = reachable;
+    }
+}
+
+// Updated system to apply highlighting directly to the entity's sprite, or
+// to the tint its chunk mesh is drawn with for tiles. Tiles are first
+// tinted by the selected unit's movement range, so a hovered tile keeps
+// showing whether it can be reached.
 fn selection_highlight_system(
-    mut query: Query<(&Selectable, &mut Sprite)>,
+    mut sprites: Query<(&Selectable, &mut Sprite, Option<&BaseTint>)>,
+    mut tiles: Query<(&Tile, &Selectable, &mut TileTint, &BaseTint), Without<Sprite>>,
+    range: Res<MovementRange>,
 ) {
-    for (selectable, mut sprite) in query.iter_mut() {
-        // Apply visual changes based on selection state
-        if selectable.is_selected {
-            // Selected state - apply a bright highlight effect
-            sprite.color = Color::rgba(1.0, 1.0, 0.6, 1.0); // Bright highlight
-        } else if selectable.is_hovered {
-            // Hover state - subtle highlight
-            sprite.color = Color::rgba(0.9, 0.9, 1.0, 1.0); // Light blue highlight
-        } else {
-            // Default state
-            sprite.color = Color::WHITE;
+    for (selectable, mut sprite, base_tint) in sprites.iter_mut() {
+        let base = base_tint.map_or(Color::WHITE, |tint| tint.0);
+        sprite.color = highlight_color(selectable, base);
+    }
+
+    for (tile, selectable, mut tint, base_tint) in tiles.iter_mut() {
+        // Only write real changes, since every change rebuilds a chunk
+        let color = highlight_color(selectable, range_color(&range, tile.hex, base_tint.0));
+        if tint.0 != color {
+            tint.0 = color;
         }
     }
-}
\ No newline at end of file
+}
+
+/// `base` lightened if the selected unit can reach `hex` and greyed out if
+/// it can't; unchanged when no unit is selected
+fn range_color(range: &MovementRange, hex: Hex, base: Color) -> Color {
+    if range.unit.is_none() {
+        return base;
+    }
+    if range.reachable.contains_key(&hex) {
+        let lighten = |channel: f32| channel + (1.0 - channel) * RANGE_LIGHTEN;
+        Color::rgba(lighten(base.r()), lighten(base.g()), lighten(base.b()), base.a())
+    } else {
+        let [red, green, blue] = RANGE_GREY_WEIGHTS;
+        let grey = (base.r() * red + base.g() * green + base.b() * blue) * RANGE_GREY_SHADE;
+        Color::rgba(grey, grey, grey, base.a())
+    }
+}
+
+fn highlight_color(selectable: &Selectable, base: Color) -> Color {
+    // Apply visual changes based on selection state
+    if selectable.is_selected {
+        // Selected state - apply a bright highlight effect
+        Color::rgba(1.0, 1.0, 0.6, 1.0) // Bright highlight
+    } else if selectable.is_hovered {
+        // Hover state - subtle highlight over the base tint
+        Color::rgba(base.r() * 0.9, base.g() * 0.9, base.b(), base.a()) // Light blue highlight
+    } else {
+        // Default state
+        base
+    }
+}
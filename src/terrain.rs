@@ -0,0 +1,894 @@
+use bevy::app::AppExit;
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use crate::resources::GameState;
+
+/// Path (relative to `assets/`) of the terrain definitions shipped with the game
+pub const TERRAIN_DEFINITIONS_PATH: &str = "data/terrains.terrain.ron";
+
+/// The shipped terrain definitions, for tests that need real terrain data
+#[cfg(test)]
+pub(crate) const SHIPPED_DEFINITIONS: &str = include_str!("../assets/data/terrains.terrain.ron");
+
+/// Terrain features that can replace a base biome during generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum TerrainFeature {
+    Mountain,
+    Cactus,
+    Rocks,
+    Forest,
+}
+
+/// Kinds of water placed below sea level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum WaterKind {
+    /// Open water connected to the map edge
+    Ocean,
+    /// Ocean next to land
+    Coast,
+    /// Enclosed water away from the map edge
+    Lake,
+}
+
+impl WaterKind {
+    pub const ALL: [WaterKind; 3] = [WaterKind::Ocean, WaterKind::Coast, WaterKind::Lake];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// How map generation produces a terrain
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum TerrainRule {
+    /// A base biome, placed where the [`ClimateTable`] names it
+    Biome,
+    /// Replaces a base biome where `feature` is placed. An empty `biomes`
+    /// list matches every biome.
+    Feature {
+        feature: TerrainFeature,
+        #[serde(default)]
+        biomes: Vec<String>,
+    },
+    /// Placed below sea level instead of a biome
+    Water { kind: WaterKind },
+}
+
+/// One terrain type as declared in the definitions file
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainDef {
+    pub id: String,
+    /// Texture paths, one per visual variant
+    pub textures: Vec<String>,
+    #[serde(default = "default_movement_cost")]
+    pub movement_cost: u32,
+    /// Fraction of incoming damage ignored by units defending on this terrain
+    #[serde(default)]
+    pub defense_bonus: f32,
+    #[serde(default = "default_passable")]
+    pub passable: bool,
+    /// Added to the sight range of units standing on this terrain
+    #[serde(default)]
+    pub vision_modifier: i32,
+    /// RGB multiplied into the textures, e.g. to colour shared art
+    #[serde(default)]
+    pub tint: Option<[f32; 3]>,
+    pub rule: TerrainRule,
+    /// Cleanup rules applied after features are placed
+    #[serde(default)]
+    pub smoothing: SmoothingRules,
+    /// What a tile of this terrain produces before any deposit on it
+    #[serde(default)]
+    pub yields: Yields,
+    /// Terrains with a higher priority bleed over the edges of neighbouring
+    /// tiles with a lower one; terrains without one keep hard edges
+    #[serde(default)]
+    pub blend_priority: Option<i32>,
+}
+
+/// Food, production and gold a tile produces each turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Yields {
+    pub food: u32,
+    pub production: u32,
+    pub gold: u32,
+}
+
+impl Yields {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Food, production and gold added together, for comparing tiles
+    pub fn total(&self) -> u32 {
+        self.food + self.production + self.gold
+    }
+}
+
+impl Add for Yields {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            food: self.food + other.food,
+            production: self.production + other.production,
+            gold: self.gold + other.gold,
+        }
+    }
+}
+
+impl AddAssign for Yields {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for Yields {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl fmt::Display for Yields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} food, {} production, {} gold", self.food, self.production, self.gold)
+    }
+}
+
+/// A kind of resource deposit, e.g. ore, and where map generation may
+/// place it
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositDef {
+    pub id: String,
+    /// Texture drawn over tiles holding the deposit
+    pub icon: String,
+    /// Added to the yields of the tile holding the deposit
+    #[serde(default)]
+    pub yields: Yields,
+    /// Terrains the deposit may be placed on
+    pub terrains: Vec<String>,
+    /// Chance, from 0 to 1, that a tile of one of those terrains gets the
+    /// deposit
+    pub chance: f32,
+    /// Deposits of the same kind are placed at least this many hexes apart
+    #[serde(default)]
+    pub min_distance: i32,
+}
+
+/// How the smoothing pass treats one terrain, as declared in the
+/// definitions file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SmoothingRules {
+    /// Connected patches of this terrain with fewer tiles are absorbed by
+    /// the terrain around them
+    #[serde(default)]
+    pub min_cluster_size: usize,
+    /// Terrains that may never border this one. Smoothing never places a
+    /// terrain next to one it conflicts with, and replaces tiles of this
+    /// terrain that already do, preferably through a transition.
+    #[serde(default)]
+    pub forbidden_neighbors: Vec<String>,
+    /// Checked in order; the first rule with a matching neighbour replaces
+    /// the tile
+    #[serde(default)]
+    pub transitions: Vec<TransitionRule>,
+}
+
+/// Tiles of the declaring terrain next to any of `neighbors` become `insert`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransitionRule {
+    pub neighbors: Vec<String>,
+    pub insert: String,
+}
+
+fn default_movement_cost() -> u32 {
+    1
+}
+
+fn default_passable() -> bool {
+    true
+}
+
+/// Whittaker-style lookup from climate to base biome.
+///
+/// Temperature and moisture both run from 0 to 1. Each list of bands holds
+/// ascending upper bounds, with one more open-ended band above the last, so
+/// `biomes` has `temperature_bands.len() + 1` rows (coldest first) of
+/// `moisture_bands.len() + 1` biome ids (driest first).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClimateTable {
+    pub temperature_bands: Vec<f32>,
+    pub moisture_bands: Vec<f32>,
+    pub biomes: Vec<Vec<String>>,
+}
+
+/// Hand-authored adjacency for the wave function collapse generator.
+///
+/// Only terrains listed here are placed. Two terrains may border each other
+/// if either one names the other, so each pair only needs listing once; a
+/// terrain that may border itself has to name itself.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WfcDefinitions {
+    pub tiles: Vec<WfcTileDef>,
+}
+
+/// One terrain the wave function collapse generator may place
+#[derive(Debug, Clone, Deserialize)]
+pub struct WfcTileDef {
+    pub terrain: String,
+    /// Relative chance of picking this terrain when a hex collapses
+    #[serde(default = "default_wfc_weight")]
+    pub weight: f32,
+    pub neighbors: Vec<String>,
+}
+
+fn default_wfc_weight() -> f32 {
+    1.0
+}
+
+/// Contents of a `.terrain.ron` file, before validation.
+///
+/// Feature rules are matched in file order, so list biome-specific
+/// variants before catch-all ones.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TerrainDefinitions {
+    pub terrains: Vec<TerrainDef>,
+    pub climate: ClimateTable,
+    /// Adjacency rules for the wave function collapse generator, if any
+    #[serde(default)]
+    pub wfc: Option<WfcDefinitions>,
+    /// Resource deposits placed on top of the terrain
+    #[serde(default)]
+    pub deposits: Vec<DepositDef>,
+}
+
+impl TerrainDefinitions {
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+    }
+}
+
+/// Interned handle to a terrain in the [`TerrainRegistry`].
+///
+/// Ids are only handed out by the registry, so looking one up never fails
+/// and comparing two is an integer compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TerrainId(u16);
+
+impl TerrainId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Interned handle to a deposit kind in the [`TerrainRegistry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DepositId(u16);
+
+impl DepositId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Problems found while loading or validating terrain definitions
+#[derive(Debug)]
+pub enum TerrainError {
+    /// The definitions file is not valid RON
+    Parse(ron::error::SpannedError),
+    /// Two terrains share an id
+    DuplicateTerrain(String),
+    /// A terrain has no texture variants to draw it with
+    MissingTextures(String),
+    /// A feature rule or the climate table names a terrain that is not a
+    /// base biome
+    UnknownBiome { terrain: String, biome: String },
+    /// A smoothing rule names a terrain that is not defined
+    UnknownTerrain { terrain: String, reference: String },
+    /// The climate table's shape does not match its bands, or the bands
+    /// are not ascending
+    InvalidClimateTable(String),
+    /// No terrain for one kind of water
+    MissingWater(WaterKind),
+    /// More than one terrain for one kind of water
+    DuplicateWater(WaterKind),
+    /// The wave function collapse rules are empty, too large, list a
+    /// terrain twice, have a weight that is not positive or border a terrain
+    /// that is not one of their tiles
+    InvalidWfcRules(String),
+    /// A deposit is defined twice, names a terrain that is not defined or
+    /// has a chance outside 0 to 1, or there are more deposits than a
+    /// [`DepositId`] can address
+    InvalidDeposit(String),
+    /// More terrains than a [`TerrainId`] can address
+    TooManyTerrains(usize),
+}
+
+impl fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainError::Parse(err) => write!(f, "invalid terrain definitions: {}", err),
+            TerrainError::DuplicateTerrain(id) => write!(f, "terrain \"{}\" is defined more than once", id),
+            TerrainError::MissingTextures(id) => write!(f, "terrain \"{}\" has no texture variants", id),
+            TerrainError::UnknownBiome { terrain, biome } => {
+                write!(f, "terrain \"{}\" refers to \"{}\", which is not a base biome", terrain, biome)
+            }
+            TerrainError::UnknownTerrain { terrain, reference } => {
+                write!(f, "terrain \"{}\" refers to \"{}\", which is not defined", terrain, reference)
+            }
+            TerrainError::InvalidClimateTable(reason) => write!(f, "invalid climate table: {}", reason),
+            TerrainError::InvalidWfcRules(reason) => write!(f, "invalid wave function collapse rules: {}", reason),
+            TerrainError::InvalidDeposit(reason) => write!(f, "invalid deposit: {}", reason),
+            TerrainError::MissingWater(kind) => write!(f, "no terrain for {:?} water", kind),
+            TerrainError::DuplicateWater(kind) => write!(f, "more than one terrain for {:?} water", kind),
+            TerrainError::TooManyTerrains(count) => write!(f, "{} terrains defined, at most {} are supported", count, u16::MAX),
+        }
+    }
+}
+
+impl std::error::Error for TerrainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TerrainError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for TerrainError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        TerrainError::Parse(err)
+    }
+}
+
+/// A feature rule with its terrain names resolved to ids
+#[derive(Debug, Clone)]
+struct FeatureRule {
+    feature: TerrainFeature,
+    /// Empty matches every biome
+    biomes: Vec<TerrainId>,
+    terrain: TerrainId,
+}
+
+/// Smoothing rules of one terrain with their terrain names resolved to ids
+#[derive(Debug, Clone, Default)]
+pub struct TerrainSmoothing {
+    pub min_cluster_size: usize,
+    pub forbidden_neighbors: Vec<TerrainId>,
+    /// Neighbours that trigger each transition, and the terrain it inserts
+    pub transitions: Vec<(Vec<TerrainId>, TerrainId)>,
+}
+
+/// Wave function collapse rules with their terrain names resolved.
+///
+/// Tiles are addressed by their position in [`WfcRules::terrains`], and
+/// sets of tiles are bit masks over those positions.
+#[derive(Debug, Clone)]
+pub struct WfcRules {
+    pub terrains: Vec<TerrainId>,
+    pub weights: Vec<f32>,
+    /// Tiles allowed next to each tile
+    neighbors: Vec<u64>,
+}
+
+impl WfcRules {
+    /// Most tiles a rule set can hold, one per bit of a mask
+    pub const MAX_TILES: usize = u64::BITS as usize;
+
+    pub fn len(&self) -> usize {
+        self.terrains.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terrains.is_empty()
+    }
+
+    /// Mask with every tile set
+    pub fn all(&self) -> u64 {
+        u64::MAX >> (Self::MAX_TILES - self.len())
+    }
+
+    /// Mask of the tiles that may border `tile`
+    pub fn neighbors(&self, tile: usize) -> u64 {
+        self.neighbors[tile]
+    }
+
+    /// Position of a terrain in the rules, if it is placed by them
+    pub fn tile(&self, terrain: TerrainId) -> Option<usize> {
+        self.terrains.iter().position(|&other| other == terrain)
+    }
+}
+
+/// Validated terrain definitions, addressed by [`TerrainId`].
+///
+/// Building a registry checks everything generation relies on, so map
+/// generation itself cannot fail on bad terrain data.
+#[derive(Resource, Debug, Clone, TypeUuid, TypePath)]
+#[uuid = "6d17c8aa-2248-4606-ac0a-3c33be9e3b38"]
+pub struct TerrainRegistry {
+    terrains: Vec<TerrainDef>,
+    ids: HashMap<String, TerrainId>,
+    temperature_bands: Vec<f32>,
+    moisture_bands: Vec<f32>,
+    /// Climate table with names resolved, one row per temperature band
+    climate: Vec<Vec<TerrainId>>,
+    /// Feature rules in file order
+    features: Vec<FeatureRule>,
+    /// Water terrains, indexed by [`WaterKind::index`]
+    water: [TerrainId; 3],
+    /// Smoothing rules, indexed by [`TerrainId::index`]
+    smoothing: Vec<TerrainSmoothing>,
+    wfc: Option<WfcRules>,
+    deposits: Vec<DepositDef>,
+    deposit_ids: HashMap<String, DepositId>,
+    /// Deposits that may be placed on each terrain, in file order, indexed
+    /// by [`TerrainId::index`]
+    terrain_deposits: Vec<Vec<DepositId>>,
+}
+
+impl TerrainRegistry {
+    pub fn new(definitions: TerrainDefinitions) -> Result<Self, TerrainError> {
+        let terrains = definitions.terrains;
+        if terrains.len() > u16::MAX as usize {
+            return Err(TerrainError::TooManyTerrains(terrains.len()));
+        }
+
+        let mut ids = HashMap::new();
+        for (index, terrain) in terrains.iter().enumerate() {
+            if terrain.textures.is_empty() {
+                return Err(TerrainError::MissingTextures(terrain.id.clone()));
+            }
+            if ids.insert(terrain.id.clone(), TerrainId(index as u16)).is_some() {
+                return Err(TerrainError::DuplicateTerrain(terrain.id.clone()));
+            }
+        }
+
+        let is_biome = |id: &TerrainId| matches!(terrains[id.index()].rule, TerrainRule::Biome);
+        let climate = resolve_climate_table(&definitions.climate, &ids, is_biome)?;
+
+        let mut features = Vec::new();
+        for (index, terrain) in terrains.iter().enumerate() {
+            if let TerrainRule::Feature { feature, biomes: biome_names } = &terrain.rule {
+                let biome_ids = biome_names.iter()
+                    .map(|name| {
+                        ids.get(name)
+                            .copied()
+                            .filter(&is_biome)
+                            .ok_or_else(|| TerrainError::UnknownBiome {
+                                terrain: terrain.id.clone(),
+                                biome: name.clone(),
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                features.push(FeatureRule {
+                    feature: *feature,
+                    biomes: biome_ids,
+                    terrain: TerrainId(index as u16),
+                });
+            }
+        }
+
+        let mut water: [Option<TerrainId>; 3] = [None; 3];
+        for (index, terrain) in terrains.iter().enumerate() {
+            if let TerrainRule::Water { kind } = terrain.rule {
+                if water[kind.index()].replace(TerrainId(index as u16)).is_some() {
+                    return Err(TerrainError::DuplicateWater(kind));
+                }
+            }
+        }
+        let mut water_ids = [TerrainId(0); 3];
+        for kind in WaterKind::ALL {
+            water_ids[kind.index()] = water[kind.index()].ok_or(TerrainError::MissingWater(kind))?;
+        }
+
+        let resolve = |terrain: &TerrainDef, name: &String| {
+            ids.get(name).copied().ok_or_else(|| TerrainError::UnknownTerrain {
+                terrain: terrain.id.clone(),
+                reference: name.clone(),
+            })
+        };
+        let resolve_all = |terrain: &TerrainDef, names: &[String]| {
+            names.iter().map(|name| resolve(terrain, name)).collect::<Result<Vec<_>, _>>()
+        };
+        let smoothing = terrains.iter()
+            .map(|terrain| {
+                let rules = &terrain.smoothing;
+                let transitions = rules.transitions.iter()
+                    .map(|rule| {
+                        Ok((resolve_all(terrain, &rule.neighbors)?, resolve(terrain, &rule.insert)?))
+                    })
+                    .collect::<Result<Vec<_>, TerrainError>>()?;
+                Ok(TerrainSmoothing {
+                    min_cluster_size: rules.min_cluster_size,
+                    forbidden_neighbors: resolve_all(terrain, &rules.forbidden_neighbors)?,
+                    transitions,
+                })
+            })
+            .collect::<Result<Vec<_>, TerrainError>>()?;
+
+        let wfc = definitions.wfc
+            .map(|wfc| resolve_wfc_rules(&wfc, &ids))
+            .transpose()?;
+
+        let deposits = definitions.deposits;
+        if deposits.len() > u16::MAX as usize {
+            return Err(TerrainError::InvalidDeposit(format!(
+                "{} deposits defined, at most {} are supported", deposits.len(), u16::MAX,
+            )));
+        }
+        let mut deposit_ids = HashMap::new();
+        let mut terrain_deposits = vec![Vec::new(); terrains.len()];
+        for (index, deposit) in deposits.iter().enumerate() {
+            let id = DepositId(index as u16);
+            if deposit_ids.insert(deposit.id.clone(), id).is_some() {
+                return Err(TerrainError::InvalidDeposit(format!("\"{}\" is defined more than once", deposit.id)));
+            }
+            if !(0.0..=1.0).contains(&deposit.chance) {
+                return Err(TerrainError::InvalidDeposit(format!(
+                    "\"{}\" needs a chance from 0 to 1", deposit.id,
+                )));
+            }
+            for name in &deposit.terrains {
+                let terrain = ids.get(name).ok_or_else(|| TerrainError::InvalidDeposit(format!(
+                    "\"{}\" is placed on \"{}\", which is not defined", deposit.id, name,
+                )))?;
+                terrain_deposits[terrain.index()].push(id);
+            }
+        }
+
+        Ok(Self {
+            terrains,
+            ids,
+            temperature_bands: definitions.climate.temperature_bands,
+            moisture_bands: definitions.climate.moisture_bands,
+            climate,
+            features,
+            water: water_ids,
+            smoothing,
+            wfc,
+            deposits,
+            deposit_ids,
+            terrain_deposits,
+        })
+    }
+
+    /// Parse and validate definitions, e.g. outside of the asset system
+    pub fn from_ron(source: &str) -> Result<Self, TerrainError> {
+        Self::new(TerrainDefinitions::from_ron(source)?)
+    }
+
+    /// Id of the terrain with the given name
+    pub fn id(&self, name: &str) -> Option<TerrainId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get(&self, id: TerrainId) -> &TerrainDef {
+        &self.terrains[id.index()]
+    }
+
+    pub fn name(&self, id: TerrainId) -> &str {
+        &self.get(id).id
+    }
+
+    pub fn len(&self) -> usize {
+        self.terrains.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terrains.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TerrainId, &TerrainDef)> {
+        self.terrains.iter()
+            .enumerate()
+            .map(|(index, terrain)| (TerrainId(index as u16), terrain))
+    }
+
+    /// Base biome for a climate, both values from 0 to 1
+    pub fn biome_for(&self, temperature: f32, moisture: f32) -> TerrainId {
+        let band = |bands: &[f32], value: f32| bands.iter().take_while(|&&bound| value > bound).count();
+        self.climate[band(&self.temperature_bands, temperature)][band(&self.moisture_bands, moisture)]
+    }
+
+    /// Terrain produced when `feature` lands on `biome`, falling back to the
+    /// biome itself when no rule matches
+    pub fn resolve(&self, biome: TerrainId, feature: Option<TerrainFeature>) -> TerrainId {
+        let Some(feature) = feature else {
+            return biome;
+        };
+
+        self.features.iter()
+            .find(|rule| {
+                rule.feature == feature && (rule.biomes.is_empty() || rule.biomes.contains(&biome))
+            })
+            .map_or(biome, |rule| rule.terrain)
+    }
+
+    /// Terrain used for a kind of water
+    pub fn water(&self, kind: WaterKind) -> TerrainId {
+        self.water[kind.index()]
+    }
+
+    pub fn is_water(&self, id: TerrainId) -> bool {
+        self.water.contains(&id)
+    }
+
+    pub fn smoothing(&self, id: TerrainId) -> &TerrainSmoothing {
+        &self.smoothing[id.index()]
+    }
+
+    /// Whether two terrains may sit next to each other; either one
+    /// forbidding the other is enough to rule it out
+    pub fn can_border(&self, a: TerrainId, b: TerrainId) -> bool {
+        !self.smoothing(a).forbidden_neighbors.contains(&b) && !self.smoothing(b).forbidden_neighbors.contains(&a)
+    }
+
+    /// Adjacency rules for the wave function collapse generator, if the
+    /// definitions have any
+    pub fn wfc_rules(&self) -> Option<&WfcRules> {
+        self.wfc.as_ref()
+    }
+
+    /// Id of the deposit kind with the given name
+    pub fn deposit_id(&self, name: &str) -> Option<DepositId> {
+        self.deposit_ids.get(name).copied()
+    }
+
+    pub fn deposit(&self, id: DepositId) -> &DepositDef {
+        &self.deposits[id.index()]
+    }
+
+    pub fn deposit_name(&self, id: DepositId) -> &str {
+        &self.deposit(id).id
+    }
+
+    pub fn deposits(&self) -> impl Iterator<Item = (DepositId, &DepositDef)> {
+        self.deposits.iter()
+            .enumerate()
+            .map(|(index, deposit)| (DepositId(index as u16), deposit))
+    }
+
+    /// Deposits that may be placed on a terrain, in file order
+    pub fn deposits_on(&self, terrain: TerrainId) -> &[DepositId] {
+        &self.terrain_deposits[terrain.index()]
+    }
+
+    /// Yields of a tile of `terrain`, with `deposit` on it if there is one
+    pub fn yields(&self, terrain: TerrainId, deposit: Option<DepositId>) -> Yields {
+        let deposit_yields = deposit.map_or(Yields::default(), |deposit| self.deposit(deposit).yields);
+        self.get(terrain).yields + deposit_yields
+    }
+
+    /// Whether units may enter the terrain
+    pub fn is_passable(&self, id: TerrainId) -> bool {
+        self.get(id).passable
+    }
+
+    /// Whether `over` is drawn bleeding over the edge it shares with a tile
+    /// of `under`
+    pub fn blends_over(&self, over: TerrainId, under: TerrainId) -> bool {
+        match (self.get(over).blend_priority, self.get(under).blend_priority) {
+            (Some(over), Some(under)) => over > under,
+            _ => false,
+        }
+    }
+}
+
+/// Check the climate table's shape and resolve its biome names
+fn resolve_climate_table(
+    table: &ClimateTable,
+    ids: &HashMap<String, TerrainId>,
+    is_biome: impl Fn(&TerrainId) -> bool,
+) -> Result<Vec<Vec<TerrainId>>, TerrainError> {
+    for (name, bands) in [("temperature", &table.temperature_bands), ("moisture", &table.moisture_bands)] {
+        if bands.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(TerrainError::InvalidClimateTable(format!("{} bands must be ascending", name)));
+        }
+    }
+    if table.biomes.len() != table.temperature_bands.len() + 1 {
+        return Err(TerrainError::InvalidClimateTable(format!(
+            "{} temperature bands need {} rows, found {}",
+            table.temperature_bands.len(), table.temperature_bands.len() + 1, table.biomes.len(),
+        )));
+    }
+
+    table.biomes.iter()
+        .map(|row| {
+            if row.len() != table.moisture_bands.len() + 1 {
+                return Err(TerrainError::InvalidClimateTable(format!(
+                    "{} moisture bands need {} biomes per row, found {}",
+                    table.moisture_bands.len(), table.moisture_bands.len() + 1, row.len(),
+                )));
+            }
+            row.iter()
+                .map(|name| {
+                    ids.get(name)
+                        .copied()
+                        .filter(&is_biome)
+                        .ok_or_else(|| TerrainError::UnknownBiome {
+                            terrain: "climate table".to_string(),
+                            biome: name.clone(),
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Check the wave function collapse rules and resolve their terrain names
+fn resolve_wfc_rules(definitions: &WfcDefinitions, ids: &HashMap<String, TerrainId>) -> Result<WfcRules, TerrainError> {
+    let tiles = &definitions.tiles;
+    if tiles.is_empty() {
+        return Err(TerrainError::InvalidWfcRules("no tiles listed".to_string()));
+    }
+    if tiles.len() > WfcRules::MAX_TILES {
+        return Err(TerrainError::InvalidWfcRules(format!(
+            "{} tiles listed, at most {} are supported", tiles.len(), WfcRules::MAX_TILES,
+        )));
+    }
+
+    let mut terrains = Vec::new();
+    for tile in tiles {
+        let id = ids.get(&tile.terrain).copied().ok_or_else(|| TerrainError::UnknownTerrain {
+            terrain: "wfc rules".to_string(),
+            reference: tile.terrain.clone(),
+        })?;
+        if terrains.contains(&id) {
+            return Err(TerrainError::InvalidWfcRules(format!("\"{}\" is listed more than once", tile.terrain)));
+        }
+        if tile.weight.is_nan() || tile.weight <= 0.0 {
+            return Err(TerrainError::InvalidWfcRules(format!("\"{}\" needs a positive weight", tile.terrain)));
+        }
+        terrains.push(id);
+    }
+
+    let mut neighbors = vec![0u64; tiles.len()];
+    for (index, tile) in tiles.iter().enumerate() {
+        for name in &tile.neighbors {
+            let other = ids.get(name)
+                .and_then(|id| terrains.iter().position(|terrain| terrain == id))
+                .ok_or_else(|| TerrainError::InvalidWfcRules(format!(
+                    "\"{}\" borders \"{}\", which is not one of the tiles", tile.terrain, name,
+                )))?;
+            neighbors[index] |= 1 << other;
+            neighbors[other] |= 1 << index;
+        }
+    }
+
+    Ok(WfcRules {
+        terrains,
+        weights: tiles.iter().map(|tile| tile.weight).collect(),
+        neighbors,
+    })
+}
+
+#[derive(Default)]
+pub struct TerrainDefinitionsLoader;
+
+impl AssetLoader for TerrainDefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let source = std::str::from_utf8(bytes)?;
+            let registry = TerrainRegistry::from_ron(source)?;
+            load_context.set_default_asset(LoadedAsset::new(registry));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["terrain.ron"]
+    }
+}
+
+/// Handle to the definitions while they load
+#[derive(Resource)]
+pub struct TerrainDefinitionsHandle(pub Handle<TerrainRegistry>);
+
+/// Registers the terrain asset type and loads the game's definitions.
+///
+/// Once loaded and validated, the [`TerrainRegistry`] is inserted as a
+/// resource and the game moves from [`GameState::Loading`] to
+/// [`GameState::LoadingTextures`].
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<TerrainRegistry>()
+            .init_asset_loader::<TerrainDefinitionsLoader>()
+            .add_systems(Startup, load_terrain_definitions)
+            .add_systems(Update, wait_for_terrain_definitions.run_if(in_state(GameState::Loading)));
+    }
+}
+
+fn load_terrain_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TerrainDefinitionsHandle(asset_server.load(TERRAIN_DEFINITIONS_PATH)));
+}
+
+fn wait_for_terrain_definitions(
+    mut commands: Commands,
+    handle: Res<TerrainDefinitionsHandle>,
+    registries: Res<Assets<TerrainRegistry>>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if let Some(registry) = registries.get(&handle.0) {
+        info!("Loaded {} terrain definitions", registry.len());
+        commands.insert_resource(registry.clone());
+        next_state.set(GameState::LoadingTextures);
+    } else if asset_server.get_load_state(&handle.0) == bevy::asset::LoadState::Failed {
+        // The asset server has already logged the TerrainError; there is no
+        // map to play without terrain, so shut down cleanly
+        error!("Failed to load terrain definitions from {}", TERRAIN_DEFINITIONS_PATH);
+        exit.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shipped definitions with `change` applied, run through validation
+    fn validate(change: impl FnOnce(&mut TerrainDefinitions)) -> Result<TerrainRegistry, TerrainError> {
+        let mut definitions = TerrainDefinitions::from_ron(SHIPPED_DEFINITIONS).unwrap();
+        change(&mut definitions);
+        TerrainRegistry::new(definitions)
+    }
+
+    #[test]
+    fn shipped_definitions_are_valid() {
+        assert!(validate(|_| ()).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_terrain_references() {
+        let result = validate(|definitions| {
+            definitions.terrains[0].smoothing.forbidden_neighbors.push("lava".to_string());
+        });
+        assert!(matches!(result, Err(TerrainError::UnknownTerrain { reference, .. }) if reference == "lava"));
+    }
+
+    #[test]
+    fn rejects_terrains_without_textures() {
+        let result = validate(|definitions| definitions.terrains[0].textures.clear());
+        assert!(matches!(result, Err(TerrainError::MissingTextures(_))));
+    }
+
+    #[test]
+    fn rejects_duplicate_wfc_tiles() {
+        let result = validate(|definitions| {
+            let tiles = &mut definitions.wfc.as_mut().unwrap().tiles;
+            tiles.push(tiles[0].clone());
+        });
+        assert!(matches!(result, Err(TerrainError::InvalidWfcRules(_))));
+    }
+
+    #[test]
+    fn rejects_zero_weight_wfc_tiles() {
+        let result = validate(|definitions| {
+            definitions.wfc.as_mut().unwrap().tiles[0].weight = 0.0;
+        });
+        assert!(matches!(result, Err(TerrainError::InvalidWfcRules(_))));
+    }
+
+    #[test]
+    fn rejects_deposit_chances_outside_zero_to_one() {
+        for chance in [-0.1, 1.5] {
+            let result = validate(|definitions| definitions.deposits[0].chance = chance);
+            assert!(matches!(result, Err(TerrainError::InvalidDeposit(_))), "chance {} was accepted", chance);
+        }
+    }
+}
@@ -1,10 +1,12 @@
+use crate::utils::Hex;
+
 /// Trait for entities that can move on the hex grid
 pub trait Moveable {
     /// Check if the entity can move to the given coordinates
-    fn can_move_to(&self, q: i32, r: i32) -> bool;
+    fn can_move_to(&self, target: Hex) -> bool;
     /// Move the entity to the given coordinates if possible
     /// Returns true if the move was successful
-    fn move_to(&mut self, q: i32, r: i32) -> bool;
+    fn move_to(&mut self, target: Hex) -> bool;
 }
 
 /// Trait for entities that can be interacted with
@@ -6,11 +6,13 @@ use bevy::prelude::*;
 pub use root::*;
 pub use menu::*;
 
+/// Panels, tooltips and menus for playing the game
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_ui_system)
+        app.add_plugins(MapInfoPlugin)
+            .add_systems(Startup, setup_ui_system)
             .add_systems(Update, (
                 update_unit_info_system,
                 handle_context_menu,
@@ -18,4 +20,15 @@ impl Plugin for UiPlugin {
                 handle_menu_interaction,
             ).chain());
     }
-}
\ No newline at end of file
+}
+
+/// The parts of the UI describing the map itself, which need none of the
+/// unit panels or menus
+pub struct MapInfoPlugin;
+
+impl Plugin for MapInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (setup_map_seed_text_system, setup_tile_tooltip_system))
+            .add_systems(Update, (update_map_seed_text_system, update_tile_tooltip_system));
+    }
+}
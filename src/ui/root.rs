@@ -1,7 +1,14 @@
 // ui/root.rs
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use crate::components::*;
+use crate::hex_map::HexMap;
 use crate::resources::SelectionState;
+use crate::systems::MapGenConfig;
+use crate::terrain::TerrainRegistry;
+
+/// Gap between the cursor and the tile tooltip
+const TOOLTIP_OFFSET: f32 = 16.0;
 
 pub fn setup_ui_system(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
@@ -91,6 +98,39 @@ fn spawn_unit_info_panel(parent: &mut ChildBuilder, asset_server: &Res<AssetServ
     });
 }
 
+/// Spawn the hidden tooltip [`update_tile_tooltip_system`] fills in
+pub fn setup_tile_tooltip_system(mut commands: Commands) {
+    // Not UiBlocking: it sits next to the cursor and must not stop the tile
+    // under it from being hovered
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(6.0)),
+                display: Display::None,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgba(0.1, 0.1, 0.1, 0.85)),
+            z_index: ZIndex::Global(1),
+            ..default()
+        },
+        TileTooltip,
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            TileTooltipText,
+        ));
+    });
+}
+
 fn spawn_details_button(parent: &mut ChildBuilder) {
     parent.spawn((
         ButtonBundle {
@@ -147,4 +187,82 @@ pub fn update_unit_info_system(
             text.sections[0].value = "No unit selected".to_string();
         }
     }
-}
\ No newline at end of file
+}
+
+/// Show the map seed in the top right corner, so a map can be reproduced
+/// from a bug report
+pub fn setup_map_seed_text_system(mut commands: Commands, map_gen_config: Res<MapGenConfig>) {
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Seed: {}", map_gen_config.seed),
+            TextStyle {
+                font_size: 16.0,
+                color: Color::GRAY,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        MapSeedText,
+    ));
+}
+
+pub fn update_map_seed_text_system(
+    map_gen_config: Res<MapGenConfig>,
+    mut seed_text_query: Query<&mut Text, With<MapSeedText>>,
+) {
+    if !map_gen_config.is_changed() {
+        return;
+    }
+
+    for mut text in seed_text_query.iter_mut() {
+        text.sections[0].value = format!("Seed: {}", map_gen_config.seed);
+    }
+}
+
+/// Show the hovered tile's terrain, deposit, defense and sight bonuses and
+/// yields next to the cursor
+pub fn update_tile_tooltip_system(
+    selection_state: Res<SelectionState>,
+    registry: Option<Res<TerrainRegistry>>,
+    hex_map: Res<HexMap>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    tiles: Query<&Tile>,
+    mut tooltip_query: Query<&mut Style, With<TileTooltip>>,
+    mut tooltip_text_query: Query<&mut Text, With<TileTooltipText>>,
+) {
+    let Ok(mut tooltip_style) = tooltip_query.get_single_mut() else {
+        return;
+    };
+
+    let hovered_tile = selection_state.hovered_entity.and_then(|entity| tiles.get(entity).ok());
+    let cursor = windows.get_single().ok().and_then(|window| window.cursor_position());
+    let (Some(tile), Some(cursor), Some(registry)) = (hovered_tile, cursor, registry) else {
+        tooltip_style.display = Display::None;
+        return;
+    };
+
+    tooltip_style.display = Display::Flex;
+    tooltip_style.left = Val::Px(cursor.x + TOOLTIP_OFFSET);
+    tooltip_style.top = Val::Px(cursor.y + TOOLTIP_OFFSET);
+
+    if let Ok(mut text) = tooltip_text_query.get_single_mut() {
+        let deposit = tile.deposit
+            .map(|deposit| format!("\nDeposit: {}", registry.deposit_name(deposit)))
+            .unwrap_or_default();
+        let defense = registry.get(tile.terrain).defense_bonus;
+        let defense = if defense != 0.0 { format!("\nDefense {:+.0}%", defense * 100.0) } else { String::new() };
+        let vision = hex_map.vision_bonus(&registry, tile.hex);
+        let vision = if vision != 0 { format!("\nSight {:+}", vision) } else { String::new() };
+        let value = format!("{}{}{}{}\n{}", registry.name(tile.terrain), deposit, defense, vision, tile.yields);
+        // Only touch the text when it changes, so it isn't laid out again
+        // every frame
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -2,7 +2,7 @@
 use bevy::prelude::*;
 use crate::components::*;
 use crate::constants::*;
-use crate::utils::axial_to_world;
+use crate::utils::Hex;
 
 #[derive(Component)]
 pub struct BaseUnit {
@@ -13,13 +13,13 @@ pub struct BaseUnit {
 }
 
 impl BaseUnit {
-    pub fn new(name: String, sprite: Handle<Image>, q: i32, r: i32) -> Self {
+    pub fn new(name: String, sprite: Handle<Image>, position: Hex) -> Self {
         Self {
             entity: GameEntity {
                 name,
                 description: "A unit".to_string(),
                 entity_type: EntityType::Unit,
-                position: (q, r),
+                position,
                 sprite,
             },
             health: super::DEFAULT_UNIT_HEALTH as f32,
@@ -29,7 +29,7 @@ impl BaseUnit {
     }
 
     #[inline]
-    pub fn position(&self) -> (i32, i32) {
+    pub fn position(&self) -> Hex {
         self.entity.position
     }
 
@@ -0,0 +1,613 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use bevy::prelude::*;
+
+/// Axial hex coordinate.
+///
+/// The third cube axis is implied (`s = -q - r`), so every `Hex` is a valid
+/// cell. Use this everywhere a grid position is passed around instead of
+/// loose `(q, r)` pairs.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Cube hex coordinate, with the invariant `q + r + s == 0`.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CubeHex {
+    pub q: i32,
+    pub r: i32,
+    pub s: i32,
+}
+
+/// Axial coordinate with fractional components, as produced by pixel
+/// conversion or interpolation. Call [`FractionalHex::round`] to get the
+/// hex that contains it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FractionalHex {
+    pub q: f64,
+    pub r: f64,
+}
+
+/// Which rows or columns are shoved by half a hex in an offset layout.
+///
+/// `OddR`/`EvenR` shift alternate rows and suit pointy-top hexes,
+/// `OddQ`/`EvenQ` shift alternate columns and suit flat-top hexes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OffsetKind {
+    #[default]
+    OddR,
+    EvenR,
+    OddQ,
+    EvenQ,
+}
+
+/// Column/row coordinate in one of the [`OffsetKind`] layouts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OffsetCoord {
+    pub col: i32,
+    pub row: i32,
+}
+
+/// Column/row coordinate in a doubled layout, where one axis steps by two
+/// so that `col + row` is always even
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DoubledCoord {
+    pub col: i32,
+    pub row: i32,
+}
+
+/// The six edge-sharing neighbour directions, named for the default
+/// pointy-top layout and listed counter-clockwise starting east.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+/// The six corner-sharing diagonal directions, listed counter-clockwise
+/// starting between `East` and `NorthEast`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HexDiagonal {
+    EastNorthEast,
+    North,
+    WestNorthWest,
+    WestSouthWest,
+    South,
+    EastSouthEast,
+}
+
+impl HexDirection {
+    /// All directions in counter-clockwise order
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast,
+    ];
+
+    /// Position of this direction in [`HexDirection::ALL`]
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Direction at `index` in [`HexDirection::ALL`], wrapping around
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL[index % 6]
+    }
+
+    /// Unit offset for a single step in this direction
+    pub fn offset(self) -> Hex {
+        match self {
+            HexDirection::East => Hex::new(1, 0),
+            HexDirection::NorthEast => Hex::new(1, -1),
+            HexDirection::NorthWest => Hex::new(0, -1),
+            HexDirection::West => Hex::new(-1, 0),
+            HexDirection::SouthWest => Hex::new(-1, 1),
+            HexDirection::SouthEast => Hex::new(0, 1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::from_index(self.index() + 3)
+    }
+
+    /// Next direction counter-clockwise
+    pub fn rotate_left(self) -> Self {
+        Self::from_index(self.index() + 1)
+    }
+
+    /// Next direction clockwise
+    pub fn rotate_right(self) -> Self {
+        Self::from_index(self.index() + 5)
+    }
+}
+
+impl HexDiagonal {
+    /// All diagonals in counter-clockwise order
+    pub const ALL: [HexDiagonal; 6] = [
+        HexDiagonal::EastNorthEast,
+        HexDiagonal::North,
+        HexDiagonal::WestNorthWest,
+        HexDiagonal::WestSouthWest,
+        HexDiagonal::South,
+        HexDiagonal::EastSouthEast,
+    ];
+
+    /// Offset to the diagonal neighbour in this direction
+    pub fn offset(self) -> Hex {
+        match self {
+            HexDiagonal::EastNorthEast => Hex::new(2, -1),
+            HexDiagonal::North => Hex::new(1, -2),
+            HexDiagonal::WestNorthWest => Hex::new(-1, -1),
+            HexDiagonal::WestSouthWest => Hex::new(-2, 1),
+            HexDiagonal::South => Hex::new(-1, 2),
+            HexDiagonal::EastSouthEast => Hex::new(1, 1),
+        }
+    }
+}
+
+impl Hex {
+    pub const ZERO: Hex = Hex::new(0, 0);
+
+    pub const fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    /// The implied third cube coordinate
+    #[inline]
+    pub const fn s(self) -> i32 {
+        -self.q - self.r
+    }
+
+    pub fn to_cube(self) -> CubeHex {
+        CubeHex::from(self)
+    }
+
+    /// Number of steps from the origin
+    pub fn length(self) -> i32 {
+        (self.q.abs() + self.r.abs() + self.s().abs()) / 2
+    }
+
+    /// Number of steps between two hexes
+    pub fn distance(self, other: Hex) -> i32 {
+        (self - other).length()
+    }
+
+    pub fn neighbor(self, direction: HexDirection) -> Hex {
+        self + direction.offset()
+    }
+
+    /// All six edge neighbours, in [`HexDirection::ALL`] order
+    pub fn neighbors(self) -> [Hex; 6] {
+        HexDirection::ALL.map(|direction| self.neighbor(direction))
+    }
+
+    pub fn diagonal_neighbor(self, diagonal: HexDiagonal) -> Hex {
+        self + diagonal.offset()
+    }
+
+    /// Rotate 60° counter-clockwise around `center`
+    pub fn rotate_left(self, center: Hex) -> Hex {
+        let offset = self - center;
+        Hex::new(-offset.s(), -offset.q) + center
+    }
+
+    /// Rotate 60° clockwise around `center`
+    pub fn rotate_right(self, center: Hex) -> Hex {
+        let offset = self - center;
+        Hex::new(-offset.r, -offset.s()) + center
+    }
+
+    /// Mirror across the q axis (swaps r and s)
+    pub fn reflect_q(self) -> Hex {
+        Hex::new(self.q, self.s())
+    }
+
+    /// Mirror across the r axis (swaps q and s)
+    pub fn reflect_r(self) -> Hex {
+        Hex::new(self.s(), self.r)
+    }
+
+    /// Mirror across the s axis (swaps q and r)
+    pub fn reflect_s(self) -> Hex {
+        Hex::new(self.r, self.q)
+    }
+}
+
+impl CubeHex {
+    pub fn new(q: i32, r: i32, s: i32) -> Self {
+        debug_assert_eq!(q + r + s, 0, "cube coordinates must satisfy q + r + s == 0");
+        Self { q, r, s }
+    }
+
+    pub fn to_axial(self) -> Hex {
+        Hex::from(self)
+    }
+
+    pub fn length(self) -> i32 {
+        (self.q.abs() + self.r.abs() + self.s.abs()) / 2
+    }
+
+    pub fn distance(self, other: CubeHex) -> i32 {
+        (self - other).length()
+    }
+
+    pub fn neighbor(self, direction: HexDirection) -> CubeHex {
+        self + direction.offset().to_cube()
+    }
+
+    /// Rotate 60° counter-clockwise around `center`
+    pub fn rotate_left(self, center: CubeHex) -> CubeHex {
+        self.to_axial().rotate_left(center.to_axial()).to_cube()
+    }
+
+    /// Rotate 60° clockwise around `center`
+    pub fn rotate_right(self, center: CubeHex) -> CubeHex {
+        self.to_axial().rotate_right(center.to_axial()).to_cube()
+    }
+
+    pub fn reflect_q(self) -> CubeHex {
+        CubeHex::new(self.q, self.s, self.r)
+    }
+
+    pub fn reflect_r(self) -> CubeHex {
+        CubeHex::new(self.s, self.r, self.q)
+    }
+
+    pub fn reflect_s(self) -> CubeHex {
+        CubeHex::new(self.r, self.q, self.s)
+    }
+}
+
+impl OffsetCoord {
+    pub const fn new(col: i32, row: i32) -> Self {
+        Self { col, row }
+    }
+}
+
+impl DoubledCoord {
+    pub const fn new(col: i32, row: i32) -> Self {
+        Self { col, row }
+    }
+}
+
+impl Hex {
+    /// Convert to column/row in the given offset layout
+    pub fn to_offset(self, kind: OffsetKind) -> OffsetCoord {
+        let (q, r) = (self.q, self.r);
+        match kind {
+            OffsetKind::OddR => OffsetCoord::new(q + (r - (r & 1)) / 2, r),
+            OffsetKind::EvenR => OffsetCoord::new(q + (r + (r & 1)) / 2, r),
+            OffsetKind::OddQ => OffsetCoord::new(q, r + (q - (q & 1)) / 2),
+            OffsetKind::EvenQ => OffsetCoord::new(q, r + (q + (q & 1)) / 2),
+        }
+    }
+
+    /// Convert from column/row in the given offset layout
+    pub fn from_offset(offset: OffsetCoord, kind: OffsetKind) -> Hex {
+        let (col, row) = (offset.col, offset.row);
+        match kind {
+            OffsetKind::OddR => Hex::new(col - (row - (row & 1)) / 2, row),
+            OffsetKind::EvenR => Hex::new(col - (row + (row & 1)) / 2, row),
+            OffsetKind::OddQ => Hex::new(col, row - (col - (col & 1)) / 2),
+            OffsetKind::EvenQ => Hex::new(col, row - (col + (col & 1)) / 2),
+        }
+    }
+
+    /// Doubled-width coordinates, pairing with pointy-top hexes
+    pub fn to_doubled_width(self) -> DoubledCoord {
+        DoubledCoord::new(2 * self.q + self.r, self.r)
+    }
+
+    pub fn from_doubled_width(doubled: DoubledCoord) -> Hex {
+        Hex::new((doubled.col - doubled.row) / 2, doubled.row)
+    }
+
+    /// Doubled-height coordinates, pairing with flat-top hexes
+    pub fn to_doubled_height(self) -> DoubledCoord {
+        DoubledCoord::new(self.q, 2 * self.r + self.q)
+    }
+
+    pub fn from_doubled_height(doubled: DoubledCoord) -> Hex {
+        Hex::new(doubled.col, (doubled.row - doubled.col) / 2)
+    }
+}
+
+impl FractionalHex {
+    pub fn new(q: f64, r: f64) -> Self {
+        Self { q, r }
+    }
+
+    /// The implied third cube coordinate
+    #[inline]
+    pub fn s(self) -> f64 {
+        -self.q - self.r
+    }
+
+    /// Linear interpolation in cube space
+    pub fn lerp(self, other: FractionalHex, t: f64) -> FractionalHex {
+        FractionalHex::new(
+            self.q + (other.q - self.q) * t,
+            self.r + (other.r - self.r) * t,
+        )
+    }
+
+    /// Round to the containing hex.
+    ///
+    /// Each cube component is rounded separately, then the one with the
+    /// largest rounding error is recomputed from the other two so that
+    /// `q + r + s == 0` still holds. Rounding q and r on their own picks
+    /// the wrong hex near edges and corners.
+    pub fn round(self) -> Hex {
+        let (q, r, s) = (self.q, self.r, self.s());
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        Hex::new(rq as i32, rr as i32)
+    }
+}
+
+impl From<Hex> for FractionalHex {
+    fn from(hex: Hex) -> Self {
+        FractionalHex::new(hex.q as f64, hex.r as f64)
+    }
+}
+
+impl From<Hex> for CubeHex {
+    fn from(hex: Hex) -> Self {
+        CubeHex::new(hex.q, hex.r, hex.s())
+    }
+}
+
+impl From<CubeHex> for Hex {
+    fn from(cube: CubeHex) -> Self {
+        Hex::new(cube.q, cube.r)
+    }
+}
+
+impl From<(i32, i32)> for Hex {
+    fn from((q, r): (i32, i32)) -> Self {
+        Hex::new(q, r)
+    }
+}
+
+impl From<Hex> for (i32, i32) {
+    fn from(hex: Hex) -> Self {
+        (hex.q, hex.r)
+    }
+}
+
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.q, self.r)
+    }
+}
+
+impl fmt::Display for CubeHex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.q, self.r, self.s)
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+    fn add(self, other: Hex) -> Hex {
+        Hex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+    fn sub(self, other: Hex) -> Hex {
+        Hex::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i32> for Hex {
+    type Output = Hex;
+    fn mul(self, factor: i32) -> Hex {
+        Hex::new(self.q * factor, self.r * factor)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Hex;
+    fn neg(self) -> Hex {
+        Hex::new(-self.q, -self.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, other: Hex) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Hex {
+    fn sub_assign(&mut self, other: Hex) {
+        *self = *self - other;
+    }
+}
+
+impl Add for FractionalHex {
+    type Output = FractionalHex;
+    fn add(self, other: FractionalHex) -> FractionalHex {
+        FractionalHex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Add for CubeHex {
+    type Output = CubeHex;
+    fn add(self, other: CubeHex) -> CubeHex {
+        CubeHex::new(self.q + other.q, self.r + other.r, self.s + other.s)
+    }
+}
+
+impl Sub for CubeHex {
+    type Output = CubeHex;
+    fn sub(self, other: CubeHex) -> CubeHex {
+        CubeHex::new(self.q - other.q, self.r - other.r, self.s - other.s)
+    }
+}
+
+impl Mul<i32> for CubeHex {
+    type Output = CubeHex;
+    fn mul(self, factor: i32) -> CubeHex {
+        CubeHex::new(self.q * factor, self.r * factor, self.s * factor)
+    }
+}
+
+impl Neg for CubeHex {
+    type Output = CubeHex;
+    fn neg(self) -> CubeHex {
+        CubeHex::new(-self.q, -self.r, -self.s)
+    }
+}
+
+/// Calculate distance between two hex coordinates
+pub fn hex_distance(a: Hex, b: Hex) -> i32 {
+    a.distance(b)
+}
+
+/// Hexes exactly `radius` steps from `center`, walking counter-clockwise
+/// from the south-west corner. A radius of 0 yields just the center.
+pub fn ring(center: Hex, radius: i32) -> impl Iterator<Item = Hex> {
+    let sides = if radius > 0 { 0..6 } else { 0..0 };
+    std::iter::once(center)
+        .filter(move |_| radius == 0)
+        .chain(sides.flat_map(move |side| {
+            let corner = center + HexDirection::from_index(side + 4).offset() * radius;
+            let direction = HexDirection::from_index(side).offset();
+            (0..radius).map(move |step| corner + direction * step)
+        }))
+}
+
+/// Rings of increasing radius around `center`, from the center outwards
+pub fn spiral(center: Hex, radius: i32) -> impl Iterator<Item = Hex> {
+    (0..=radius).flat_map(move |ring_radius| ring(center, ring_radius))
+}
+
+/// Every hex within `n` steps of `center`, ordered by q then r
+pub fn range(center: Hex, n: i32) -> impl Iterator<Item = Hex> {
+    (-n..=n).flat_map(move |q| {
+        let r1 = (-n).max(-q - n);
+        let r2 = n.min(-q + n);
+        (r1..=r2).map(move |r| center + Hex::new(q, r))
+    })
+}
+
+/// Every hex within `n_a` steps of `a` and within `n_b` steps of `b`
+pub fn range_intersection(a: Hex, n_a: i32, b: Hex, n_b: i32) -> impl Iterator<Item = Hex> {
+    let q_min = (a.q - n_a).max(b.q - n_b);
+    let q_max = (a.q + n_a).min(b.q + n_b);
+    let r_min = (a.r - n_a).max(b.r - n_b);
+    let r_max = (a.r + n_a).min(b.r + n_b);
+    let s_min = (a.s() - n_a).max(b.s() - n_b);
+    let s_max = (a.s() + n_a).min(b.s() + n_b);
+
+    (q_min..=q_max).flat_map(move |q| {
+        let r1 = r_min.max(-q - s_max);
+        let r2 = r_max.min(-q - s_min);
+        (r1..=r2).map(move |r| Hex::new(q, r))
+    })
+}
+
+/// Hexes on the straight line from `a` to `b`, both ends included.
+///
+/// Samples are nudged by a tiny epsilon so a line running exactly along
+/// hex edges never lands on a vertex tie and always rounds the same way.
+pub fn line(a: Hex, b: Hex) -> impl Iterator<Item = Hex> {
+    const EPSILON: FractionalHex = FractionalHex { q: 1e-6, r: 2e-6 };
+
+    let steps = a.distance(b);
+    let start = FractionalHex::from(a) + EPSILON;
+    let end = FractionalHex::from(b) + EPSILON;
+
+    (0..=steps).map(move |i| {
+        let t = if steps == 0 { 0.0 } else { i as f64 / steps as f64 };
+        start.lerp(end, t).round()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_has_six_hexes_per_step_of_radius() {
+        let center = Hex::new(2, -3);
+        assert_eq!(ring(center, 0).collect::<Vec<_>>(), vec![center]);
+        for radius in 1..=6 {
+            let hexes: Vec<Hex> = ring(center, radius).collect();
+            assert_eq!(hexes.len(), 6 * radius as usize);
+            assert!(hexes.iter().all(|hex| hex.distance(center) == radius));
+        }
+    }
+
+    #[test]
+    fn round_picks_the_right_hex_next_to_a_corner() {
+        // Each corner of the origin is shared with two neighbours; points a
+        // hair away from it towards any of the three centres belong to that
+        // hex, which rounding q and r on their own gets wrong
+        for side in 0..6 {
+            let hexes = [
+                Hex::ZERO,
+                HexDirection::from_index(side).offset(),
+                HexDirection::from_index(side + 1).offset(),
+            ];
+            let corner = FractionalHex::new(
+                hexes.iter().map(|hex| hex.q as f64).sum::<f64>() / 3.0,
+                hexes.iter().map(|hex| hex.r as f64).sum::<f64>() / 3.0,
+            );
+            for hex in hexes {
+                let near = corner.lerp(FractionalHex::from(hex), 0.05);
+                assert_eq!(near.round(), hex, "near corner {:?}", corner);
+            }
+        }
+    }
+
+    #[test]
+    fn line_settles_ties_the_same_way_in_both_directions() {
+        // The midpoint of this line sits exactly on the edge between
+        // (1, 0) and (0, 1)
+        let a = Hex::ZERO;
+        let b = Hex::new(1, 1);
+        let forward: Vec<Hex> = line(a, b).collect();
+        let mut backward: Vec<Hex> = line(b, a).collect();
+        backward.reverse();
+
+        assert_eq!(forward.len(), 3);
+        assert_eq!(forward, backward);
+        assert!([Hex::new(1, 0), Hex::new(0, 1)].contains(&forward[1]));
+        for pair in forward.windows(2) {
+            assert_eq!(pair[0].distance(pair[1]), 1);
+        }
+    }
+
+    #[test]
+    fn offset_and_doubled_coordinates_round_trip() {
+        let kinds = [OffsetKind::OddR, OffsetKind::EvenR, OffsetKind::OddQ, OffsetKind::EvenQ];
+        for hex in spiral(Hex::new(1, -2), 5) {
+            for kind in kinds {
+                assert_eq!(Hex::from_offset(hex.to_offset(kind), kind), hex, "{:?}", kind);
+            }
+            assert_eq!(Hex::from_doubled_width(hex.to_doubled_width()), hex);
+            assert_eq!(Hex::from_doubled_height(hex.to_doubled_height()), hex);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use super::coordinates::{Hex, HexDirection};
+
+/// The side shared by two neighbouring hexes.
+///
+/// The pair is stored in sorted order, so the edge between `a` and `b` is
+/// the same value whichever side it is looked up from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HexEdge {
+    a: Hex,
+    b: Hex,
+}
+
+/// A corner shared by three mutually adjacent hexes, stored sorted.
+///
+/// Hexes in the triple may lie outside the map; corners on the map border
+/// still have all three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HexVertex {
+    hexes: [Hex; 3],
+}
+
+impl HexEdge {
+    /// Edge between two neighbouring hexes
+    pub fn new(a: Hex, b: Hex) -> Self {
+        debug_assert_eq!(a.distance(b), 1, "{} and {} are not neighbours", a, b);
+        if a <= b {
+            Self { a, b }
+        } else {
+            Self { a: b, b: a }
+        }
+    }
+
+    /// Edge on the `direction` side of `hex`
+    pub fn between(hex: Hex, direction: HexDirection) -> Self {
+        Self::new(hex, hex.neighbor(direction))
+    }
+
+    /// The two hexes either side of the edge
+    pub fn hexes(&self) -> (Hex, Hex) {
+        (self.a, self.b)
+    }
+
+    pub fn contains(&self, hex: Hex) -> bool {
+        self.a == hex || self.b == hex
+    }
+
+    /// The two corners at the ends of the edge
+    pub fn vertices(&self) -> [HexVertex; 2] {
+        let offset = self.b - self.a;
+        [
+            HexVertex::new(self.a, self.b, self.a + offset.rotate_left(Hex::ZERO)),
+            HexVertex::new(self.a, self.b, self.a + offset.rotate_right(Hex::ZERO)),
+        ]
+    }
+}
+
+impl HexVertex {
+    /// Corner shared by three mutually adjacent hexes
+    pub fn new(a: Hex, b: Hex, c: Hex) -> Self {
+        debug_assert!(
+            a.distance(b) == 1 && b.distance(c) == 1 && a.distance(c) == 1,
+            "{}, {} and {} do not meet at a corner", a, b, c
+        );
+        let mut hexes = [a, b, c];
+        hexes.sort();
+        Self { hexes }
+    }
+
+    /// Corner of `hex` between `direction` and the next direction
+    /// counter-clockwise
+    pub fn corner(hex: Hex, direction: HexDirection) -> Self {
+        Self::new(hex, hex.neighbor(direction), hex.neighbor(direction.rotate_left()))
+    }
+
+    pub fn hexes(&self) -> [Hex; 3] {
+        self.hexes
+    }
+
+    /// The three edges meeting at this corner
+    pub fn edges(&self) -> [HexEdge; 3] {
+        let [a, b, c] = self.hexes;
+        [HexEdge::new(a, b), HexEdge::new(b, c), HexEdge::new(a, c)]
+    }
+
+    /// Adjacent corners, each with the edge leading to it
+    pub fn neighbors(&self) -> [(HexVertex, HexEdge); 3] {
+        let [a, b, c] = self.hexes;
+        // The far corner of each edge swaps the third hex for its mirror
+        // image across that edge
+        [
+            (HexVertex::new(a, b, a + b - c), HexEdge::new(a, b)),
+            (HexVertex::new(b, c, b + c - a), HexEdge::new(b, c)),
+            (HexVertex::new(a, c, a + c - b), HexEdge::new(a, c)),
+        ]
+    }
+}
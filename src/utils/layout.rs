@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use crate::constants::{HEX_SIZE, SQRT_3};
+use super::coordinates::{FractionalHex, Hex};
+use super::edge::{HexEdge, HexVertex};
+
+/// Forward and inverse matrices for converting between axial coordinates
+/// and world space, plus the angle of the first corner in sixths of a turn.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexOrientation {
+    pub f0: f32,
+    pub f1: f32,
+    pub f2: f32,
+    pub f3: f32,
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub b3: f32,
+    pub start_angle: f32,
+}
+
+impl HexOrientation {
+    /// Hexes with a corner at the top and rows offset horizontally
+    pub const POINTY: HexOrientation = HexOrientation {
+        f0: SQRT_3,
+        f1: SQRT_3 / 2.0,
+        f2: 0.0,
+        f3: 3.0 / 2.0,
+        b0: SQRT_3 / 3.0,
+        b1: -1.0 / 3.0,
+        b2: 0.0,
+        b3: 2.0 / 3.0,
+        start_angle: 0.5,
+    };
+
+    /// Hexes with an edge at the top and columns offset vertically
+    pub const FLAT: HexOrientation = HexOrientation {
+        f0: 3.0 / 2.0,
+        f1: 0.0,
+        f2: SQRT_3 / 2.0,
+        f3: SQRT_3,
+        b0: 2.0 / 3.0,
+        b1: 0.0,
+        b2: -1.0 / 3.0,
+        b3: SQRT_3 / 3.0,
+        start_angle: 0.0,
+    };
+
+    pub fn is_pointy(&self) -> bool {
+        *self == Self::POINTY
+    }
+
+    /// Look an orientation up by name, "pointy" or "flat", ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "pointy" => Some(Self::POINTY),
+            "flat" => Some(Self::FLAT),
+            _ => None,
+        }
+    }
+}
+
+/// How the hex grid is laid out in world space.
+///
+/// `size` is the centre-to-corner distance per axis, so a non-uniform size
+/// squashes every hex (useful under the tilted isometric camera), and
+/// `origin` is the world position of hex (0, 0).
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct HexLayout {
+    pub orientation: HexOrientation,
+    pub size: Vec2,
+    pub origin: Vec2,
+}
+
+impl Default for HexLayout {
+    fn default() -> Self {
+        Self::pointy(HEX_SIZE)
+    }
+}
+
+impl HexLayout {
+    /// Regular hexes of `size` centred on the world origin
+    pub fn new(orientation: HexOrientation, size: f32) -> Self {
+        Self {
+            orientation,
+            size: Vec2::splat(size),
+            origin: Vec2::ZERO,
+        }
+    }
+
+    pub fn pointy(size: f32) -> Self {
+        Self::new(HexOrientation::POINTY, size)
+    }
+
+    pub fn flat(size: f32) -> Self {
+        Self::new(HexOrientation::FLAT, size)
+    }
+
+    /// Centre of a hex in world space
+    pub fn hex_to_world(&self, hex: Hex) -> Vec3 {
+        let m = &self.orientation;
+        let (q, r) = (hex.q as f32, hex.r as f32);
+        let x = (m.f0 * q + m.f1 * r) * self.size.x;
+        let y = (m.f2 * q + m.f3 * r) * self.size.y;
+        Vec3::new(x + self.origin.x, y + self.origin.y, 0.0)
+    }
+
+    /// Convert a world position on the grid plane to fractional axial coordinates.
+    ///
+    /// Positions are taken in world space: the mirrored y scale on tile
+    /// sprites only flips their texture around the tile centre, so callers
+    /// must not un-flip a point before converting it.
+    pub fn world_to_fractional(&self, position: Vec3) -> FractionalHex {
+        let m = &self.orientation;
+        let x = ((position.x - self.origin.x) / self.size.x) as f64;
+        let y = ((position.y - self.origin.y) / self.size.y) as f64;
+        let q = m.b0 as f64 * x + m.b1 as f64 * y;
+        let r = m.b2 as f64 * x + m.b3 as f64 * y;
+        FractionalHex::new(q, r)
+    }
+
+    /// Hex containing a world position
+    pub fn world_to_hex(&self, position: Vec3) -> Hex {
+        self.world_to_fractional(position).round()
+    }
+
+    /// Offset of corner `corner` (0..6) from a hex centre, counter-clockwise
+    pub fn corner_offset(&self, corner: usize) -> Vec2 {
+        let angle = std::f32::consts::TAU * (self.orientation.start_angle + corner as f32) / 6.0;
+        Vec2::new(self.size.x * angle.cos(), self.size.y * angle.sin())
+    }
+
+    /// World positions of the six corners of a hex
+    pub fn corners(&self, hex: Hex) -> [Vec2; 6] {
+        let center = self.hex_to_world(hex).truncate();
+        std::array::from_fn(|corner| center + self.corner_offset(corner))
+    }
+
+    /// World position of a corner shared by three hexes
+    pub fn vertex_to_world(&self, vertex: HexVertex) -> Vec2 {
+        // The corner sits at the centroid of the three hex centres, which
+        // holds for any orientation and size since the layout is affine
+        let sum: Vec2 = vertex.hexes().iter().map(|&hex| self.hex_to_world(hex).truncate()).sum();
+        sum / 3.0
+    }
+
+    /// World positions of the two ends of an edge
+    pub fn edge_to_world(&self, edge: HexEdge) -> (Vec2, Vec2) {
+        let [start, end] = edge.vertices();
+        (self.vertex_to_world(start), self.vertex_to_world(end))
+    }
+
+    /// Width and height of the box bounding a single hex
+    pub fn hex_extent(&self) -> Vec2 {
+        if self.orientation.is_pointy() {
+            Vec2::new(SQRT_3 * self.size.x, 2.0 * self.size.y)
+        } else {
+            Vec2::new(2.0 * self.size.x, SQRT_3 * self.size.y)
+        }
+    }
+
+    /// Unrotated size for a pointy-top tile texture so that, after
+    /// [`HexLayout::tile_sprite_rotation`], it exactly covers one hex
+    pub fn tile_sprite_size(&self) -> Vec2 {
+        let extent = self.hex_extent();
+        if self.orientation.is_pointy() {
+            extent
+        } else {
+            Vec2::new(extent.y, extent.x)
+        }
+    }
+
+    /// Rotation around z that turns pointy-top tile art into this orientation
+    pub fn tile_sprite_rotation(&self) -> f32 {
+        if self.orientation.is_pointy() {
+            0.0
+        } else {
+            std::f32::consts::FRAC_PI_2
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use super::layout::HexLayout;
+
+/// Generate a hexagon mesh matching the layout's orientation and size
+pub fn hexagon_mesh(layout: &HexLayout) -> Mesh {
+    let (vertices, indices) = generate_hex_vertices_and_indices(layout);
+    create_mesh_from_vertices(vertices, indices, layout.size)
+}
+
+/// Generate the border of a hexagon as a band `width` thick lying just
+/// inside its edges, so neighbouring outlines meet as one line `2 * width`
+/// thick
+pub fn hexagon_outline_mesh(layout: &HexLayout, width: f32) -> Mesh {
+    let corners: Vec<Vec2> = (0..6).map(|i| layout.corner_offset(i)).collect();
+
+    // Distance from the centre to the middle of an edge; pulling every
+    // corner in by the same share keeps the band evenly thick
+    let inradius = ((corners[0] + corners[1]) / 2.0).length();
+    let inset = (1.0 - width / inradius).max(0.0);
+
+    // Outer corners first, then the matching inner ones
+    let mut vertices: Vec<[f32; 3]> = corners.iter().map(|corner| [corner.x, corner.y, 0.0]).collect();
+    vertices.extend(corners.iter().map(|corner| [corner.x * inset, corner.y * inset, 0.0]));
+
+    let indices: Vec<u32> = (0..6u32)
+        .flat_map(|i| {
+            let next = (i + 1) % 6;
+            [i, next, next + 6, i, next + 6, i + 6]
+        })
+        .collect();
+
+    create_mesh_from_vertices(vertices, indices, layout.size)
+}
+
+fn generate_hex_vertices_and_indices(layout: &HexLayout) -> (Vec<[f32; 3]>, Vec<u32>) {
+    // Generate vertex positions
+    let positions: Vec<[f32; 3]> = (0..6)
+        .map(|i| {
+            let corner = layout.corner_offset(i);
+            [corner.x, corner.y, 0.0]
+        })
+        .collect();
+
+    // Add center vertex
+    let mut vertices = vec![[0.0, 0.0, 0.0]];
+    vertices.extend_from_slice(&positions);
+
+    // Generate indices
+    let indices: Vec<u32> = (1..=6)
+        .flat_map(|i| {
+            vec![
+                0,
+                i as u32,
+                if i < 6 { (i + 1) as u32 } else { 1 },
+            ]
+        })
+        .collect();
+
+    (vertices, indices)
+}
+
+fn create_mesh_from_vertices(vertices: Vec<[f32; 3]>, indices: Vec<u32>, size: Vec2) -> Mesh {
+    let normals = vec![[0.0, 0.0, 1.0]; vertices.len()];
+    let uvs: Vec<[f32; 2]> = vertices
+        .iter()
+        .map(|[x, y, _]| {
+            let u = (*x / (size.x * 2.0)) + 0.5;
+            let v = (*y / (size.y * 2.0)) + 0.5;
+            [u, v]
+        })
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    mesh
+}
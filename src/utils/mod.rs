@@ -0,0 +1,17 @@
+/// Coordinate types and conversion functions
+pub mod coordinates;
+/// Hex edges and corners, for data that lives between tiles
+pub mod edge;
+/// Hex layout (orientation, size and origin) for world conversions
+pub mod layout;
+/// Mesh generation functions
+pub mod mesh;
+/// Map outlines (hexagon, rectangle, parallelogram, triangle)
+pub mod shape;
+
+// Re-export commonly used functions at the module level
+pub use coordinates::*;
+pub use edge::*;
+pub use layout::*;
+pub use mesh::*;
+pub use shape::*;
@@ -0,0 +1,54 @@
+use crate::constants::GRID_RADIUS;
+use super::coordinates::{range, Hex, OffsetCoord, OffsetKind};
+
+/// Overall outline of a generated map.
+///
+/// Every shape is roughly centred on hex (0, 0) so the camera starts over
+/// the middle of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapShape {
+    /// All hexes within `radius` steps of the centre
+    Hexagon { radius: i32 },
+    /// `width` columns by `height` rows in the given offset layout
+    Rectangle { width: i32, height: i32, offset: OffsetKind },
+    /// `width` steps along q by `height` steps along r
+    Parallelogram { width: i32, height: i32 },
+    /// Triangle with `size` + 1 hexes along each side
+    Triangle { size: i32 },
+}
+
+impl Default for MapShape {
+    fn default() -> Self {
+        MapShape::Hexagon { radius: GRID_RADIUS }
+    }
+}
+
+impl MapShape {
+    /// Every hex in the shape, in a stable order
+    pub fn hexes(&self) -> Vec<Hex> {
+        match *self {
+            MapShape::Hexagon { radius } => range(Hex::ZERO, radius).collect(),
+            MapShape::Rectangle { width, height, offset } => {
+                let (left, top) = (-width / 2, -height / 2);
+                (top..top + height)
+                    .flat_map(|row| {
+                        (left..left + width)
+                            .map(move |col| Hex::from_offset(OffsetCoord::new(col, row), offset))
+                    })
+                    .collect()
+            }
+            MapShape::Parallelogram { width, height } => {
+                let (q0, r0) = (-width / 2, -height / 2);
+                (q0..q0 + width)
+                    .flat_map(|q| (r0..r0 + height).map(move |r| Hex::new(q, r)))
+                    .collect()
+            }
+            MapShape::Triangle { size } => {
+                let shift = size / 3;
+                (0..=size)
+                    .flat_map(|q| (0..=size - q).map(move |r| Hex::new(q - shift, r - shift)))
+                    .collect()
+            }
+        }
+    }
+}